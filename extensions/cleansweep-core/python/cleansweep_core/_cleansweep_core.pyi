@@ -4,10 +4,29 @@
 from os import PathLike
 from typing import Any, Tuple, TypeAlias
 
+class HashAlgo:
+    """Hash algorithm used to derive question IDs."""
+
+    Md5: HashAlgo
+    Sha256: HashAlgo
+
+class IndexStyle:
+    """Array index notation used in token paths.
+
+    Dot produces paths like `$.items.0.name`; Bracket produces
+    `$.items[0].name` to match JSONPath-style mapping files.
+    """
+
+    Dot: IndexStyle
+    Bracket: IndexStyle
+
 def process_merge_results(
-    results: list[str],
+    results: list[str | None],
     frame_records: list[list[dict[str, Any]]],
     cluster_ids: list[str],
+    hash_algo: HashAlgo | None = None,
+    uuid_namespace: str | None = None,
+    dedupe: bool = False,
 ) -> list[dict[str, Any]]:
     """Process merge results from API and return merged DataFrame.
 
@@ -15,6 +34,11 @@ def process_merge_results(
         results (list): List of results from API
         frame_records (list): List of DataFrame records
         cluster_ids (list): List of cluster IDs
+        hash_algo (HashAlgo, Optional): Digest used for question IDs; defaults to HashAlgo.Md5.
+        uuid_namespace (str, Optional): Namespace UUID for deterministic v5 question UUIDs;
+            random v4 UUIDs when omitted.
+        dedupe (bool): Collapse questions sharing a question_id, aggregating their
+            source IDs into a `source_ids` list; defaults to False.
 
     Returns:
         list: List of merged DataFrame records
@@ -26,24 +50,71 @@ Token: TypeAlias = Tuple[str, bool | str | int | float]
 
 StrPath: TypeAlias = str | PathLike[str]
 
+class TokenStream:
+    """Iterator yielding one list of tokens per NDJSON record."""
+
+    def __iter__(self) -> TokenStream:
+        """Return the iterator itself."""
+
+    def __next__(self) -> list[Token]:
+        """Return the tokens of the next NDJSON record.
+
+        Returns:
+            list: List of tokens for the record
+
+        """
+
 class Tokenizer:
     """Tokenizer class for document transformation."""
 
-    def tokenize_document(self, path: StrPath, root: str | None = None) -> list[Token]:
+    def __init__(self, index_style: IndexStyle | None = None) -> None:
+        """Create a new Tokenizer.
+
+        Args:
+            index_style (IndexStyle, Optional): Array path notation; defaults to IndexStyle.Dot.
+
+        """
+
+    def tokenize_document(
+        self, path: StrPath, root: str | None = None, strict: bool = False
+    ) -> list[list[Token]]:
         """Tokenize a document using the given path.
 
         Args:
             path (StrPath): Path of file for tokenization.
             root (str, Optional): Root JSON Path value for tokenization.
+            strict (bool): Raise a ValueError on the first malformed NDJSON line
+                instead of silently skipping it; defaults to False.
+
+        Returns:
+            list: List of tokens per record in the document
+
+        """
+
+    def tokenize_ndjson_streaming(self, path: StrPath, root: str | None = None) -> TokenStream:
+        """Tokenize an NDJSON file one record at a time.
+
+        Args:
+            path (StrPath): Path of NDJSON file for tokenization.
+            root (str, Optional): Root JSON Path value applied to each record.
 
         Returns:
-            list: List of tokens
+            TokenStream: Iterator yielding one list of tokens per record
 
         """
 
 class Transformer:
     """Transformer class for document transformation."""
 
+    def __init__(self, index_style: IndexStyle | None = None) -> None:
+        """Create a new Transformer.
+
+        Args:
+            index_style (IndexStyle, Optional): Array path notation shared with the
+                tokenizer; defaults to IndexStyle.Dot.
+
+        """
+
     def transform_document(
         self, mapping_path: StrPath, document_path: StrPath, root: str | None = None
     ) -> list[dict[str, Any]]:
@@ -58,3 +129,38 @@ class Transformer:
             list: List of transformed documents
 
         """
+
+    def transform_document_from_str(
+        self, mapping_json: str, document_json: str, root: str | None = None
+    ) -> list[dict[str, Any]]:
+        """Transform an in-memory document using the given mapping and document JSON strings.
+
+        Args:
+            mapping_json (str): Mapping as a JSON string.
+            document_json (str): Document as a JSON string.
+            root (str, Optional): Root JSON Path value for transformation.
+
+        Returns:
+            list: List of transformed documents
+
+        """
+
+    def transform_document_to_csv(
+        self,
+        mapping_path: StrPath,
+        document_path: StrPath,
+        root: str | None = None,
+        output_path: StrPath | None = None,
+    ) -> str:
+        """Transform a document and flatten the result into CSV.
+
+        Args:
+            mapping_path (StrPath): Path of mapping file.
+            document_path (StrPath): Path of document file.
+            root (str, Optional): Root JSON Path value for transformation.
+            output_path (StrPath, Optional): File to also write the CSV to.
+
+        Returns:
+            str: The transformed documents as a CSV string
+
+        """
@@ -1,10 +1,10 @@
-use log::debug;
+use flate2::read::GzDecoder;
+use log::{debug, warn};
 use pyo3::{prelude::*, IntoPyObjectExt};
 use pyo3::types::{PyDict, PyList};
 use serde_json::Value as JSONValue;
 use std::fs;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::{self, Read};
 use std::path::Path;
 
 /// Convert a serde_json::Value to a PyObject.
@@ -60,65 +60,272 @@ pub fn serde_value_to_pyobject(py: Python, value: &JSONValue) -> PyObject {
 
 
 /// Reads a JSON or NDJSON file and returns a JSONValue.
-/// 
+///
 /// If the file ends with `.ndjson` or `.nd.json`, it is treated as newline-delimited JSON (NDJSON).
 /// If the file ends with `.json`, the function first attempts to parse it as a standard JSON file.
 /// If parsing fails, it then checks if it is NDJSON.
 ///
+/// Gzip-compressed files (a `.gz` extension or the gzip magic bytes) are decompressed
+/// transparently, so `.json.gz` and `.ndjson.gz` behave like their uncompressed forms.
+///
 /// # Arguments
 ///
 /// * `path` - A reference to the path of the JSON file.
 ///
 /// # Returns
 ///
-/// A JSONValue representing the JSON document.
+/// A JSONValue representing the JSON document, or an `io::Error` if the file
+/// cannot be read or does not contain valid JSON/NDJSON.
 ///
 /// # Examples
 ///
 /// ```
-/// let document = read_to_serde_value("data.json");
+/// let document = read_to_serde_value("data.json")?;
 /// ```
-pub fn read_to_serde_value(path: &str) -> JSONValue {
+pub fn read_to_serde_value(path: &str) -> io::Result<JSONValue> {
+    read_to_serde_value_with_errors(path).map(|(value, _)| value)
+}
+
+/// Variant of [`read_to_serde_value`] that also reports malformed NDJSON lines.
+///
+/// The second element of the returned pair lists `(line_number, error_message)`
+/// for each line that failed to parse; it is empty for JSON and YAML files.
+pub fn read_to_serde_value_with_errors(path: &str) -> io::Result<(JSONValue, Vec<(usize, String)>)> {
     let path = Path::new(path);
 
-    let is_ndjson = path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| ext == "ndjson" || ext == "nd.json")
-        .unwrap_or(false);
+    // Extension check on the uncompressed name, so "data.ndjson.gz" is NDJSON
+    let file_name = path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+    let file_name = file_name.strip_suffix(".gz").unwrap_or(file_name);
+    let is_ndjson = file_name.ends_with(".ndjson") || file_name.ends_with(".nd.json");
+    let is_yaml = file_name.ends_with(".yaml") || file_name.ends_with(".yml");
+
+    // Read the file contents, decompressing gzip transparently
+    let content = read_file_content(path)?;
 
     // First, handle explicitly marked NDJSON files
     if is_ndjson {
-        return read_ndjson(path);
+        return Ok(parse_ndjson_with_errors(&content));
     }
 
-    // Read the file contents
-    let content = match fs::read_to_string(path) {
-        Ok(data) => data,
-        Err(e) => panic!("Error reading file {}: {}", path.display(), e),
-    };
+    // YAML documents parse into the same JSONValue the rest of the crate uses
+    if is_yaml {
+        return parse_yaml(&content).map(|value| (value, Vec::new()));
+    }
 
     // Attempt to parse as a single JSON object
     match serde_json::from_str::<JSONValue>(&content) {
-        Ok(json) => json,
-        Err(_) => {
+        Ok(json) => Ok((json, Vec::new())),
+        Err(e) => {
             // If parsing as JSON fails, try as NDJSON
             debug!("File {} is not valid JSON, attempting NDJSON parsing.", path.display());
-            read_ndjson(path)
+            let (lines, errors) = parse_ndjson_with_errors(&content);
+            match lines.as_array() {
+                Some(arr) if !arr.is_empty() => Ok((lines, errors)),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("File {} is not valid JSON or NDJSON: {}", path.display(), e),
+                )),
+            }
         }
     }
 }
 
-/// Reads an NDJSON file and returns a JSONValue::Array
-fn read_ndjson(path: &Path) -> JSONValue {
-    let file = File::open(path).expect("Failed to open file");
-    let reader = io::BufReader::new(file);
+/// Reads a file into a string, decompressing it when the path has a `.gz`
+/// extension or the contents start with the gzip magic bytes.
+fn read_file_content(path: &Path) -> io::Result<String> {
+    let bytes = fs::read(path)?;
+
+    let is_gzip = path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext == "gz")
+        .unwrap_or(false)
+        || bytes.starts_with(&[0x1f, 0x8b]);
+
+    if is_gzip {
+        let mut decoder = GzDecoder::new(&bytes[..]);
+        let mut content = String::new();
+        decoder.read_to_string(&mut content)?;
+        Ok(content)
+    } else {
+        String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Parses YAML content into a JSONValue.
+///
+/// A multi-document stream is returned as a JSONValue::Array of the documents;
+/// a single document is returned as-is. Scalars such as dates come through as
+/// strings, so downstream tokenization is unchanged.
+fn parse_yaml(content: &str) -> io::Result<JSONValue> {
+    use serde::Deserialize;
+
+    let mut documents: Vec<JSONValue> = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let value = JSONValue::deserialize(document)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid YAML: {}", e)))?;
+        documents.push(value);
+    }
+
+    if documents.len() == 1 {
+        Ok(documents.remove(0))
+    } else {
+        Ok(JSONValue::Array(documents))
+    }
+}
+
+/// Parses NDJSON content and returns a JSONValue::Array together with the
+/// lines that failed to parse.
+///
+/// Blank lines are ignored; every other malformed line is skipped and
+/// reported as a 1-based `(line_number, error_message)` pair. A `warn!` with
+/// the total skipped count is logged when any line was dropped.
+fn parse_ndjson_with_errors(content: &str) -> (JSONValue, Vec<(usize, String)>) {
+    let mut json_lines: Vec<JSONValue> = Vec::new();
+    let mut errors: Vec<(usize, String)> = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<JSONValue>(line) {
+            Ok(value) => json_lines.push(value),
+            Err(e) => errors.push((index + 1, e.to_string())),
+        }
+    }
+
+    if !errors.is_empty() {
+        warn!("Skipped {} malformed NDJSON line(s)", errors.len());
+    }
+
+    (JSONValue::Array(json_lines), errors)
+}
 
-    let json_lines: Vec<JSONValue> = reader.lines()
-        .filter_map(|line| {
-            line.ok()
-                .and_then(|l| serde_json::from_str::<JSONValue>(&l).ok())
-        })
-        .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
 
-    JSONValue::Array(json_lines)
+    fn temp_file(name: &str, content: &str) -> std::path::PathBuf {
+        let path = env::temp_dir().join(format!("{}-{}", uuid::Uuid::new_v4(), name));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn read_missing_file_returns_not_found() {
+        let err = read_to_serde_value("/nonexistent/path.json").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn read_invalid_json_returns_invalid_data() {
+        let path = temp_file("bad.json", "{not json at all");
+        let err = read_to_serde_value(path.to_str().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn read_valid_json_and_ndjson() {
+        let path = temp_file("good.json", r#"{"a": 1}"#);
+        let value = read_to_serde_value(path.to_str().unwrap()).unwrap();
+        assert_eq!(value["a"], 1);
+        fs::remove_file(path).unwrap();
+
+        let path = temp_file("good.ndjson", "{\"a\": 1}\n{\"a\": 2}\n");
+        let value = read_to_serde_value(path.to_str().unwrap()).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 2);
+        fs::remove_file(path).unwrap();
+    }
+
+    fn temp_gz_file(name: &str, content: &str) -> std::path::PathBuf {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let path = env::temp_dir().join(format!("{}-{}", uuid::Uuid::new_v4(), name));
+        let mut encoder = GzEncoder::new(fs::File::create(&path).unwrap(), flate2::Compression::default());
+        encoder.write_all(content.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn read_gzipped_json() {
+        let path = temp_gz_file("doc.json.gz", r#"{"a": 1}"#);
+        let value = read_to_serde_value(path.to_str().unwrap()).unwrap();
+        assert_eq!(value["a"], 1);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn read_gzipped_ndjson() {
+        let path = temp_gz_file("doc.ndjson.gz", "{\"a\": 1}\n{\"a\": 2}\n");
+        let value = read_to_serde_value(path.to_str().unwrap()).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 2);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn read_yaml_matches_json_equivalent() {
+        let yaml_path = temp_file("doc.yaml", "id: '1'\ntitle: first\ncreated: 2024-01-02\nitems:\n  - a\n  - b\n");
+        let json_path = temp_file("doc.json", r#"{"id": "1", "title": "first", "created": "2024-01-02", "items": ["a", "b"]}"#);
+
+        let yaml_value = read_to_serde_value(yaml_path.to_str().unwrap()).unwrap();
+        let json_value = read_to_serde_value(json_path.to_str().unwrap()).unwrap();
+        assert_eq!(yaml_value, json_value);
+
+        fs::remove_file(yaml_path).unwrap();
+        fs::remove_file(json_path).unwrap();
+    }
+
+    #[test]
+    fn read_yaml_top_level_sequence() {
+        let path = temp_file("docs.yml", "- name: a\n- name: b\n");
+        let value = read_to_serde_value(path.to_str().unwrap()).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 2);
+        assert_eq!(value[0]["name"], "a");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn read_yaml_multi_document_stream() {
+        let path = temp_file("docs.yaml", "name: a\n---\nname: b\n");
+        let value = read_to_serde_value(path.to_str().unwrap()).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 2);
+        assert_eq!(value[1]["name"], "b");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn ndjson_malformed_lines_are_reported() {
+        let path = temp_file("partial.ndjson", "{\"a\": 1}\n{\"a\": 2}\n{\"a\": \n");
+        let (value, errors) = read_to_serde_value_with_errors(path.to_str().unwrap()).unwrap();
+
+        // the two good lines are kept, the truncated one is reported
+        assert_eq!(value.as_array().unwrap().len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 3);
+        assert!(!errors[0].1.is_empty());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn ndjson_blank_lines_are_not_errors() {
+        let (value, errors) = parse_ndjson_with_errors("{\"a\": 1}\n\n{\"a\": 2}\n");
+        assert_eq!(value.as_array().unwrap().len(), 2);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn read_gzip_detected_by_magic_bytes() {
+        // a .json file that actually holds gzip bytes should still decompress
+        let path = temp_gz_file("doc.json", r#"{"a": 1}"#);
+        let value = read_to_serde_value(path.to_str().unwrap()).unwrap();
+        assert_eq!(value["a"], 1);
+        fs::remove_file(path).unwrap();
+    }
 }
@@ -2,6 +2,8 @@ use log::{debug, warn, error};
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 use md5::{Md5, Digest};
+use rayon::prelude::*;
+use sha2::Sha256;
 use std::convert::Infallible;
 use std::fmt::Write; // For formatting the hash as a string
 use std::fmt;
@@ -10,6 +12,15 @@ mod common;
 mod tokenize;
 mod transform;
 
+/// Hash algorithm used to derive `question_id` from the question/answer pair.
+#[pyclass(eq, eq_int, module="cleansweep_core._cleansweep_core")]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum HashAlgo {
+    #[default]
+    Md5,
+    Sha256,
+}
+
 #[derive(serde::Deserialize)]
 struct ConsolidatedQuestion {
     #[serde(default)]
@@ -21,27 +32,28 @@ struct ConsolidatedQuestion {
 
 impl ConsolidatedQuestion {
     /// Generates a unique question ID by combining the question and answer fields,
-    /// creating an MD5 hash of the combined string, and converting the hash result
-    /// into a hexadecimal string.
+    /// hashing the combined string with the selected algorithm, and converting the
+    /// hash result into a hexadecimal string.
     ///
     /// # Returns
     ///
-    /// A `String` representing the MD5 hash of the combined question and answer.
+    /// A `String` representing the digest of the combined question and answer.
     ///
     /// # Panics
     ///
     /// This function will panic if it is unable to write to the string during the
     /// conversion of the hash result to a hexadecimal string.
-    fn question_id(&self) -> String {
+    fn question_id(&self, hash_algo: HashAlgo) -> String {
         // Combine the question and answer into a single string
         let combined = format!("question: {}|answer: {}", self.question, self.answer);
 
-        // Create an MD5 hash of the combined string
-        let mut hasher = Md5::new();
-        hasher.update(combined.as_bytes());
+        // Hash the combined string with the selected algorithm
+        let result = match hash_algo {
+            HashAlgo::Md5 => Md5::digest(combined.as_bytes()).to_vec(),
+            HashAlgo::Sha256 => Sha256::digest(combined.as_bytes()).to_vec(),
+        };
 
         // Convert the hash result into a hexadecimal string
-        let result = hasher.finalize();
         let mut hash_string = String::new();
         for byte in result {
             write!(&mut hash_string, "{:02x}", byte).expect("Unable to write to string");
@@ -53,12 +65,23 @@ impl ConsolidatedQuestion {
 
     /// Creates a UUID for the question.
     ///
+    /// When a namespace is supplied, the UUID is a deterministic v5 derived from
+    /// the same question/answer string that `question_id` hashes, so identical
+    /// pairs yield identical UUIDs across runs. Without a namespace the UUID is
+    /// a random v4.
+    ///
     /// # Returns
     ///
     /// A `String` representing the UUID of the question.
-    fn question_uuid(&self) -> String {
+    fn question_uuid(&self, namespace: Option<uuid::Uuid>) -> String {
         // Create a UUID for the question
-        let uuid = uuid::Uuid::new_v4();
+        let uuid = match namespace {
+            Some(namespace) => {
+                let combined = format!("question: {}|answer: {}", self.question, self.answer);
+                uuid::Uuid::new_v5(&namespace, combined.as_bytes())
+            }
+            None => uuid::Uuid::new_v4(),
+        };
         uuid.to_string()
     }
 
@@ -76,14 +99,15 @@ struct Question {
     question: String,
     answer: String,
     source_id: String,
+    source_ids: Vec<String>,
     cluster_id: String,
-    is_sufficient: bool, 
+    is_sufficient: bool,
     metadata_language: String
 }
 
 
 
-impl<'a, 'py> IntoPyObject<'py> for &'a Question {
+impl<'py> IntoPyObject<'py> for &Question {
     type Target = PyDict;
     type Output = Bound<'py, Self::Target>;
     type Error = Infallible;
@@ -96,6 +120,9 @@ impl<'a, 'py> IntoPyObject<'py> for &'a Question {
         dict.set_item("question", &self.question).unwrap();
         dict.set_item("answer", &self.answer).unwrap();
         dict.set_item("source_id", &self.source_id).unwrap();
+        if !self.source_ids.is_empty() {
+            dict.set_item("source_ids", &self.source_ids).unwrap();
+        }
         dict.set_item("cluster_id", &self.cluster_id).unwrap();
         dict.set_item("is_sufficient", self.is_sufficient).unwrap();
         dict.set_item("metadata_language", &self.metadata_language).unwrap();
@@ -113,6 +140,7 @@ impl fmt::Debug for Question {
             .field("question", &self.question)
             .field("answer", &self.answer)
             .field("source_id", &self.source_id)
+            .field("source_ids", &self.source_ids)
             .field("cluster_id", &self.cluster_id)
             .field("is_sufficient", &self.is_sufficient)
             .field("metadata_language", &self.metadata_language)
@@ -126,13 +154,123 @@ impl fmt::Display for Question {
     }
 }
 
+/// Merges a single cluster's consolidated result with its frame records.
+///
+/// Deserialises the JSON result, matches each `source_id` against the frame
+/// and builds the merged `Question` objects for the cluster. Clusters without
+/// a result pass their frame records through unchanged.
+fn merge_cluster(cluster_id: &str, result: &Option<String>, frame: &[Question], hash_algo: HashAlgo, uuid_namespace: Option<uuid::Uuid>) -> Vec<Question> {
+    let mut qa_objects: Vec<Question> = Vec::new();
+
+    if let Some(result) = result {
+
+        // let result: Result<Value, serde_json::Error> = serde_json::from_str(&result);
+        let _result: Result<ConsolidatedResponse, serde_json::Error> = serde_json::from_str(result);
+        if let Err(e) = _result {
+            error!("Error deserialising result: {} ({})", e, result);
+            return qa_objects;
+        }
+        if let Ok(deserialised_result) = _result {
+            // let mut qa_objects_cluster: Vec<Question> = Vec::new();
+            for consolidated_question in deserialised_result.items.iter() {
+                for source_id in &consolidated_question.source_ids {
+                    let source_question = frame.iter().find(|x| x.question_id == *source_id);
+                    if let Some(source_question) = source_question {
+                        let sufficient_ids = match consolidated_question.sufficient_ids.clone()  {
+                            Some(ids) => ids,
+                            None => consolidated_question.source_ids.clone()
+                        };
+                        let is_sufficient = sufficient_ids.contains(source_id);
+
+                        qa_objects.push(Question {
+                            question_id: consolidated_question.question_id(hash_algo),
+                            question_uuid: consolidated_question.question_uuid(uuid_namespace),
+                            question: consolidated_question.question.clone(),
+                            answer: consolidated_question.answer.clone(),
+                            source_id: source_question.source_id.clone(),
+                            source_ids: Vec::new(),
+                            is_sufficient,
+                            metadata_language: source_question.metadata_language.clone(),
+                            cluster_id: cluster_id.to_string(),
+                        });
+                    } else {
+                        let source_question = frame.iter().find(|x| x.question_id == consolidated_question.question_id(hash_algo));
+                        if let Some(source_question) = source_question {
+                            qa_objects.push(Question {
+                                question_id: consolidated_question.question_id(hash_algo),
+                                question_uuid: consolidated_question.question_uuid(uuid_namespace),
+                                question: consolidated_question.question.clone(),
+                                answer: consolidated_question.answer.clone(),
+                                source_id: source_question.source_id.clone(),
+                                source_ids: Vec::new(),
+                                is_sufficient: true,
+                                metadata_language: source_question.metadata_language.clone(),
+                                cluster_id: cluster_id.to_string(),
+                            });
+                        } else {
+                            warn!("No matching question found for question_id: {}", consolidated_question.question_id(hash_algo));
+                        }
+                    }
+                }
+            }
+            // qa_objects.push(qa_objects_cluster);
+        }
+
+    } else {
+        qa_objects.extend(frame.to_vec());
+    }
+
+    qa_objects
+}
+
+/// Collapses Questions sharing a `question_id` into one per id.
+///
+/// The first occurrence's fields are kept, `source_id`s are aggregated into
+/// the `source_ids` list (unique, in first-seen order) and `is_sufficient`
+/// is true if any collapsed source was sufficient. Output order follows the
+/// first occurrence of each `question_id`.
+fn dedupe_questions(qa_objects: Vec<Question>) -> Vec<Question> {
+    let mut deduped: Vec<Question> = Vec::new();
+    let mut index_by_id: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+
+    for question in qa_objects {
+        match index_by_id.get(&question.question_id) {
+            Some(&i) => {
+                let existing = &mut deduped[i];
+                if !existing.source_ids.contains(&question.source_id) {
+                    existing.source_ids.push(question.source_id);
+                }
+                existing.is_sufficient |= question.is_sufficient;
+            }
+            None => {
+                index_by_id.insert(question.question_id.clone(), deduped.len());
+                let mut question = question;
+                question.source_ids = vec![question.source_id.clone()];
+                deduped.push(question);
+            }
+        }
+    }
+
+    deduped
+}
+
 /// @parameters
 /// results: list[str | None] - serialised JSON response from OpenAI Chat API
 /// frame_recors: list[list[dict]] - list of list of dictionaries containing question_id, question, answer, source_id
 /// cluster_ids: list[int] - list of cluster ids
+/// hash_algo: HashAlgo | None - digest used for question_id, defaults to HashAlgo.Md5
+/// uuid_namespace: str | None - namespace UUID for deterministic v5 question_uuids; random v4 when omitted
+/// dedupe: bool - collapse questions sharing a question_id, aggregating their source_ids; defaults to False
 #[pyfunction]
-#[pyo3(signature = (results, frame_records, cluster_ids))]
-fn process_merge_results(py: Python, results: &'_ Bound<'_, PyList>, frame_records: &'_ Bound<'_, PyList>, cluster_ids: Vec<String>) -> PyResult<Py<PyList>> {
+#[pyo3(signature = (results, frame_records, cluster_ids, hash_algo=None, uuid_namespace=None, dedupe=false))]
+fn process_merge_results(py: Python, results: &'_ Bound<'_, PyList>, frame_records: &'_ Bound<'_, PyList>, cluster_ids: Vec<String>, hash_algo: Option<HashAlgo>, uuid_namespace: Option<String>, dedupe: bool) -> PyResult<Py<PyList>> {
+    let hash_algo = hash_algo.unwrap_or_default();
+    let uuid_namespace = match uuid_namespace {
+        Some(namespace) => Some(uuid::Uuid::parse_str(&namespace).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Invalid uuid_namespace: {}", e))
+        })?),
+        None => None,
+    };
     // init_logger();
     
     // convert inputs to rust types
@@ -155,6 +293,7 @@ fn process_merge_results(py: Python, results: &'_ Bound<'_, PyList>, frame_recor
                     question,
                     answer,
                     source_id,
+                    source_ids: Vec::new(),
                     cluster_id,
                     is_sufficient: false,
                     metadata_language
@@ -164,69 +303,20 @@ fn process_merge_results(py: Python, results: &'_ Bound<'_, PyList>, frame_recor
         frame_list
     }).collect();
 
-    // iterate over results and frame records to merge the results
-    let mut qa_objects: Vec<Question> = Vec::new();
-
-    for ((cluster_id, result), frame) in cluster_ids.iter().zip(results.iter()).zip(frame_records.iter()) {
-
-        if let Some(result) = result {
-            
-            // let result: Result<Value, serde_json::Error> = serde_json::from_str(&result);
-            let _result: Result<ConsolidatedResponse, serde_json::Error> = serde_json::from_str(result);
-            if let Err(e) = _result {
-                error!("Error deserialising result: {} ({})", e, result);
-                continue;
-            }
-            if let Ok(deserialised_result) = _result {
-                // let mut qa_objects_cluster: Vec<Question> = Vec::new();
-                for consolidated_question in deserialised_result.items.iter() {
-                    for source_id in &consolidated_question.source_ids {
-                        let source_question = frame.iter().find(|x| x.question_id == *source_id);
-                        if let Some(source_question) = source_question {
-                            let sufficient_ids = match consolidated_question.sufficient_ids.clone()  {
-                                Some(ids) => ids,
-                                None => consolidated_question.source_ids.clone()
-                            };
-                            let is_sufficient = sufficient_ids.contains(source_id);
-
-                            qa_objects.push(Question {
-                                question_id: consolidated_question.question_id(),
-                                question_uuid: consolidated_question.question_uuid(),
-                                question: consolidated_question.question.clone(),
-                                answer: consolidated_question.answer.clone(),
-                                source_id: source_question.source_id.clone(),
-                                is_sufficient,
-                                metadata_language: source_question.metadata_language.clone(),
-                                cluster_id: cluster_id.clone(),
-                            });
-                        } else {
-                            let source_question = frame.iter().find(|x| x.question_id == consolidated_question.question_id());
-                            if let Some(source_question) = source_question {
-                                qa_objects.push(Question {
-                                    question_id: consolidated_question.question_id(),
-                                    question_uuid: consolidated_question.question_uuid(),
-                                    question: consolidated_question.question.clone(),
-                                    answer: consolidated_question.answer.clone(),
-                                    source_id: source_question.source_id.clone(),
-                                    is_sufficient: true,
-                                    metadata_language: source_question.metadata_language.clone(),
-                                    cluster_id: cluster_id.clone(),
-                                });
-                            } else {
-                                warn!("No matching question found for question_id: {}", consolidated_question.question_id());
-                            }
-                        }
-                    }
-                }
-                // qa_objects.push(qa_objects_cluster);
-            }
-
-        } else {
-            qa_objects.extend(frame.clone());
-        }
-        
-
-    }
+    // merge each cluster in parallel; flat_map preserves the input cluster order
+    let qa_objects: Vec<Question> = py.allow_threads(|| {
+        cluster_ids.par_iter()
+            .zip(results.par_iter())
+            .zip(frame_records.par_iter())
+            .flat_map(|((cluster_id, result), frame)| merge_cluster(cluster_id, result, frame, hash_algo, uuid_namespace))
+            .collect()
+    });
+
+    let qa_objects = if dedupe {
+        dedupe_questions(qa_objects)
+    } else {
+        qa_objects
+    };
 
     debug!("Created {} questions", qa_objects.len());
 
@@ -241,7 +331,169 @@ fn process_merge_results(py: Python, results: &'_ Bound<'_, PyList>, frame_recor
 fn _cleansweep_core(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     pyo3_log::init();
     let _ = m.add_function(wrap_pyfunction!(process_merge_results, m)?);
+    m.add_class::<HashAlgo>()?;
+    m.add_class::<tokenize::tokenizer::IndexStyle>()?;
     m.add_class::<tokenize::tokenizer::PyTokenizer>()?;
+    m.add_class::<tokenize::tokenizer::PyTokenStream>()?;
     m.add_class::<transform::transformer::PyTransformer>()?;
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_question() -> ConsolidatedQuestion {
+        ConsolidatedQuestion {
+            source_ids: vec![],
+            sufficient_ids: None,
+            question: "What is Rust?".to_string(),
+            answer: "A systems programming language.".to_string(),
+        }
+    }
+
+    #[test]
+    fn question_id_default_matches_md5() {
+        let question = sample_question();
+        assert_eq!(
+            question.question_id(HashAlgo::default()),
+            "b814e33f18cf6a7cf99287d720a64110"
+        );
+    }
+
+    /// Comparable view of a Question, excluding the random question_uuid.
+    fn question_key(q: &Question) -> (String, String, String, String, String, bool, String) {
+        (
+            q.question_id.clone(),
+            q.question.clone(),
+            q.answer.clone(),
+            q.source_id.clone(),
+            q.cluster_id.clone(),
+            q.is_sufficient,
+            q.metadata_language.clone(),
+        )
+    }
+
+    #[test]
+    fn parallel_merge_matches_serial_on_synthetic_clusters() {
+        let n_clusters = 2000;
+        let mut cluster_ids: Vec<String> = Vec::new();
+        let mut results: Vec<Option<String>> = Vec::new();
+        let mut frame_records: Vec<Vec<Question>> = Vec::new();
+
+        for i in 0..n_clusters {
+            cluster_ids.push(format!("cluster-{}", i));
+            let source_id = format!("src-{}", i);
+            frame_records.push(vec![Question {
+                question_id: source_id.clone(),
+                question_uuid: String::new(),
+                question: format!("q-{}", i),
+                answer: format!("a-{}", i),
+                source_id: source_id.clone(),
+                source_ids: Vec::new(),
+                cluster_id: format!("cluster-{}", i),
+                is_sufficient: false,
+                metadata_language: "en".to_string(),
+            }]);
+            // every third cluster has no result and passes its frame through
+            if i % 3 == 0 {
+                results.push(None);
+            } else {
+                results.push(Some(format!(
+                    r#"{{"items": [{{"source_ids": ["{}"], "question": "merged q-{}", "answer": "merged a-{}"}}]}}"#,
+                    source_id, i, i
+                )));
+            }
+        }
+
+        let serial: Vec<Question> = cluster_ids.iter()
+            .zip(results.iter())
+            .zip(frame_records.iter())
+            .flat_map(|((cluster_id, result), frame)| merge_cluster(cluster_id, result, frame, HashAlgo::Md5, None))
+            .collect();
+
+        let parallel: Vec<Question> = cluster_ids.par_iter()
+            .zip(results.par_iter())
+            .zip(frame_records.par_iter())
+            .flat_map(|((cluster_id, result), frame)| merge_cluster(cluster_id, result, frame, HashAlgo::Md5, None))
+            .collect();
+
+        assert_eq!(serial.len(), parallel.len());
+        let serial_keys: Vec<_> = serial.iter().map(question_key).collect();
+        let parallel_keys: Vec<_> = parallel.iter().map(question_key).collect();
+        assert_eq!(serial_keys, parallel_keys);
+    }
+
+    #[test]
+    fn question_uuid_v5_is_stable_across_invocations() {
+        let question = sample_question();
+        let namespace = uuid::Uuid::parse_str("6ba7b810-9dad-11d1-80b4-00c04fd430c8").unwrap();
+
+        let first = question.question_uuid(Some(namespace));
+        let second = question.question_uuid(Some(namespace));
+        assert_eq!(first, second);
+
+        // without a namespace the UUID stays random v4
+        assert_ne!(question.question_uuid(None), question.question_uuid(None));
+    }
+
+    fn make_question(question_id: &str, source_id: &str, cluster_id: &str, is_sufficient: bool) -> Question {
+        Question {
+            question_id: question_id.to_string(),
+            question_uuid: String::new(),
+            question: format!("q-{}", question_id),
+            answer: format!("a-{}", question_id),
+            source_id: source_id.to_string(),
+            source_ids: Vec::new(),
+            cluster_id: cluster_id.to_string(),
+            is_sufficient,
+            metadata_language: "en".to_string(),
+        }
+    }
+
+    #[test]
+    fn dedupe_collapses_multi_source_questions() {
+        let questions = vec![
+            make_question("qid-1", "src-a", "cluster-0", false),
+            make_question("qid-1", "src-b", "cluster-0", true),
+            make_question("qid-2", "src-c", "cluster-0", false),
+        ];
+
+        let deduped = dedupe_questions(questions);
+        assert_eq!(deduped.len(), 2);
+        assert_eq!(deduped[0].question_id, "qid-1");
+        assert_eq!(deduped[0].source_id, "src-a");
+        assert_eq!(deduped[0].source_ids, vec!["src-a", "src-b"]);
+        assert!(deduped[0].is_sufficient);
+        assert_eq!(deduped[1].source_ids, vec!["src-c"]);
+        assert!(!deduped[1].is_sufficient);
+    }
+
+    #[test]
+    fn dedupe_collapses_across_clusters() {
+        let questions = vec![
+            make_question("qid-1", "src-a", "cluster-0", false),
+            make_question("qid-2", "src-b", "cluster-1", false),
+            make_question("qid-1", "src-c", "cluster-1", false),
+            // duplicate source in a later cluster is not repeated
+            make_question("qid-1", "src-a", "cluster-2", false),
+        ];
+
+        let deduped = dedupe_questions(questions);
+        assert_eq!(deduped.len(), 2);
+        // first occurrence wins for the retained fields
+        assert_eq!(deduped[0].cluster_id, "cluster-0");
+        assert_eq!(deduped[0].source_ids, vec!["src-a", "src-c"]);
+        assert_eq!(deduped[1].question_id, "qid-2");
+    }
+
+    #[test]
+    fn question_id_sha256_is_stable_and_distinct() {
+        let question = sample_question();
+        let sha = question.question_id(HashAlgo::Sha256);
+        assert_eq!(
+            sha,
+            "1ee5382a47f13b1441e14f0fd9ed9d25a190c89941acdd8fe6726df9ff8af4fb"
+        );
+        assert_ne!(sha, question.question_id(HashAlgo::Md5));
+    }
+}
@@ -1,18 +1,64 @@
-use crate::common::utils::{read_to_serde_value, serde_value_to_pyobject};
+use crate::common::utils::{read_to_serde_value_with_errors, serde_value_to_pyobject};
 use log::debug;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use serde_json::Value as JSONValue;
+use std::fs::File;
+use std::io::{self, BufRead};
 
 pub type PyToken = (String, PyObject);
 pub type Token = (String, JSONValue);
 
+/// Array index notation used in token paths.
+///
+/// `Dot` produces paths like `$.items.0.name`; `Bracket` produces
+/// `$.items[0].name` to match JSONPath-style mapping files.
+#[pyclass(eq, eq_int, module="cleansweep_core._cleansweep_core")]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum IndexStyle {
+    #[default]
+    Dot,
+    Bracket,
+}
+
+impl IndexStyle {
+    /// Formats an array index as a path segment in this style.
+    pub fn index_segment(&self, index: usize) -> String {
+        match self {
+            IndexStyle::Dot => format!(".{}", index),
+            IndexStyle::Bracket => format!("[{}]", index),
+        }
+    }
+
+    /// Regex fragment matching a single array index in this style.
+    pub fn index_pattern(&self) -> &'static str {
+        match self {
+            IndexStyle::Dot => "\\.\\d+",
+            IndexStyle::Bracket => "\\[\\d+\\]",
+        }
+    }
+}
+
+/// A single segment of a parsed root path: an object key or an array index.
+#[derive(Debug, PartialEq)]
+enum RootSegment {
+    Key(String),
+    Index(usize),
+}
+
 /// Tokenizer struct that tokenizes a JSON document.
-pub struct Tokenizer;
+#[derive(Clone, Copy, Default)]
+pub struct Tokenizer {
+    index_style: IndexStyle,
+}
 
 impl Tokenizer {
 
-    fn tokenize_list(list: Vec<JSONValue>, root: Option<String>) -> Vec<Token> {
+    pub fn new(index_style: IndexStyle) -> Self {
+        Tokenizer { index_style }
+    }
+
+    fn tokenize_list(&self, list: Vec<JSONValue>, root: Option<String>) -> Vec<Token> {
 
         let root = root.unwrap_or_else(|| "$".to_string());
         // let list = document.as_array().unwrap();
@@ -22,21 +68,24 @@ impl Tokenizer {
             // tokenize the items returning a vector of tokens
             let mut tokens: Vec<Token> = Vec::new();
             for (index, value) in list.iter().enumerate() {
-                let path = format!("{}.{}", root, index);
-                let nested_tokens = Tokenizer::tokenize(value, Some(path.clone()));
+                let path = format!("{}{}", root, self.index_style.index_segment(index));
+                let nested_tokens = self.tokenize(value, Some(path.clone()));
                 tokens.extend(nested_tokens);
             }
             return tokens;
         }
 
         // if all items are lists then recursively tokenize the items
-        if list.iter().all(|item| item.is_object()) {
-            // tokenize the items returning a vector of tokens
+        if list.iter().all(|item| item.is_array()) {
+            // tokenize each element of the nested lists with an index-suffixed path
             let mut tokens: Vec<Token> = Vec::new();
             for (index, value) in list.iter().enumerate() {
-                let path = format!("{}.{}", root, index);
-                let nested_tokens = Tokenizer::tokenize_list(value.as_array().unwrap().clone(), Some(path.clone()));
-                tokens.extend(nested_tokens);
+                let path = format!("{}{}", root, self.index_style.index_segment(index));
+                for (inner_index, inner_value) in value.as_array().unwrap().iter().enumerate() {
+                    let inner_path = format!("{}{}", path, self.index_style.index_segment(inner_index));
+                    let nested_tokens = self.tokenize(inner_value, Some(inner_path));
+                    tokens.extend(nested_tokens);
+                }
             }
             return tokens;
         }
@@ -63,10 +112,10 @@ impl Tokenizer {
     /// use json::JsonValue;
     ///
     /// let document = JSONValue::from_string(r#"{"name": "John", "age": 30}"#).unwrap();
-    /// let tokens = Tokenizer::tokenize(&document, None);
+    /// let tokens = Tokenizer::default().tokenize(&document, None);
     /// assert_eq!(tokens.len(), 2);
     /// ```
-    fn tokenize(document: &JSONValue, root: Option<String>) -> Vec<Token> {
+    fn tokenize(&self, document: &JSONValue, root: Option<String>) -> Vec<Token> {
         let root = root.unwrap_or_else(|| "$".to_string());
         let mut tokens: Vec<Token> = Vec::new();
 
@@ -75,7 +124,7 @@ impl Tokenizer {
             for (key, value) in map.iter() {
                 let key = key.to_string();
                 let path = format!("{}.{}", root, key);
-                let nested_tokens = Tokenizer::tokenize(value, Some(path.clone()));
+                let nested_tokens = self.tokenize(value, Some(path.clone()));
                 tokens.extend(nested_tokens);
             }
             }
@@ -85,7 +134,7 @@ impl Tokenizer {
                 //     let nested_tokens = Tokenizer::tokenize(value, Some(path.clone()));
                 //     tokens.extend(nested_tokens);
                 // }
-                tokens.extend(Tokenizer::tokenize_list(list.clone(), Some(root)));
+                tokens.extend(self.tokenize_list(list.clone(), Some(root)));
             }
             _ => {
                 tokens.push((root, document.clone()));
@@ -95,42 +144,163 @@ impl Tokenizer {
         tokens
     }
 
+    /// Parses a root path like `$.data[2].items` into key and index segments.
+    ///
+    /// Unquoted brackets hold array indices; keys containing literal dots can
+    /// be written bracket-quoted, as in `$['a.b'].items` or `$["a.b"]`.
+    fn parse_root_path(root: &str) -> Result<Vec<RootSegment>, String> {
+        let chars: Vec<char> = root.chars().collect();
+        let mut segments: Vec<RootSegment> = Vec::new();
+        let mut i = usize::from(chars.first() == Some(&'$'));
+
+        while i < chars.len() {
+            match chars[i] {
+                '.' => {
+                    i += 1;
+                    let start = i;
+                    while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+                        i += 1;
+                    }
+                    if i == start {
+                        return Err(format!("Empty key segment in root path '{}'", root));
+                    }
+                    segments.push(RootSegment::Key(chars[start..i].iter().collect()));
+                }
+                '[' => {
+                    i += 1;
+                    if matches!(chars.get(i), Some('\'') | Some('"')) {
+                        // bracket-quoted key, allowing dots inside the key
+                        let quote = chars[i];
+                        i += 1;
+                        let start = i;
+                        while i < chars.len() && chars[i] != quote {
+                            i += 1;
+                        }
+                        if i >= chars.len() {
+                            return Err(format!("Unterminated quote in root path '{}'", root));
+                        }
+                        segments.push(RootSegment::Key(chars[start..i].iter().collect()));
+                        i += 1;
+                        if chars.get(i) != Some(&']') {
+                            return Err(format!("Expected ']' after quoted key in root path '{}'", root));
+                        }
+                        i += 1;
+                    } else {
+                        let start = i;
+                        while i < chars.len() && chars[i] != ']' {
+                            i += 1;
+                        }
+                        if i >= chars.len() {
+                            return Err(format!("Unterminated '[' in root path '{}'", root));
+                        }
+                        let index = chars[start..i].iter().collect::<String>();
+                        let index = index.parse::<usize>()
+                            .map_err(|_| format!("Invalid array index '{}' in root path '{}'", index, root))?;
+                        segments.push(RootSegment::Index(index));
+                        i += 1;
+                    }
+                }
+                _ => {
+                    // a bare leading key without the `$.` prefix
+                    let start = i;
+                    while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+                        i += 1;
+                    }
+                    segments.push(RootSegment::Key(chars[start..i].iter().collect()));
+                }
+            }
+        }
+
+        Ok(segments)
+    }
+
+    /// Resolves a root path against a document, returning the subtree it names.
+    ///
+    /// Returns an error message when the path is malformed or any segment does
+    /// not exist in the document.
+    fn resolve_root<'a>(document: &'a JSONValue, root: &str) -> Result<&'a JSONValue, String> {
+        let mut current = document;
+        for segment in Tokenizer::parse_root_path(root)? {
+            current = match &segment {
+                RootSegment::Key(key) => current.get(key.as_str()),
+                RootSegment::Index(index) => current.get(index),
+            }.ok_or_else(|| format!("Root path '{}' does not resolve: segment {:?} not found", root, segment))?;
+        }
+        Ok(current)
+    }
+
+    /// Tokenizes a single NDJSON line, applying the optional root path to the record.
+    ///
+    /// Returns `None` for lines that are not valid JSON or where the root path does
+    /// not resolve, so callers can skip them the way `read_ndjson` does.
+    fn tokenize_ndjson_line(&self, line: &str, root: &Option<String>) -> Option<Vec<Token>> {
+        let mut record: JSONValue = serde_json::from_str(line).ok()?;
+
+        if let Some(root) = root {
+            record = Tokenizer::resolve_root(&record, root).ok()?.clone();
+        }
+
+        Some(self.tokenize(&record, Some("$".to_string())))
+    }
 
     /// Tokenize a JSON document and return a vector of tokens.
-    /// 
+    ///
     /// # Arguments
-    /// 
+    ///
     /// * `path` - A reference to the path of the JSON document.
     /// * `root` - An optional string representing the root path of the document.
-    /// 
+    /// * `strict` - When true, a malformed NDJSON line raises an error instead
+    ///   of being skipped.
+    ///
     /// # Returns
-    /// 
+    ///
     /// A vector of tokens representing the tokenized JSON document.
-    /// 
+    ///
     /// # Examples
-    /// 
+    ///
     /// ```
-    /// let tokenizer = Tokenizer::new();
-    /// let tokens = tokenizer.tokenize_document("data.json", None);
+    /// let tokenizer = Tokenizer::default();
+    /// let tokens = tokenizer.tokenize_document("data.json", &None, false);
     /// ```
-    pub fn tokenize_document(path: &str, root: &Option<String>) -> PyResult<Vec<Vec<Token>>> {
-        let mut document: JSONValue = read_to_serde_value(path);
+    pub fn tokenize_document(&self, path: &str, root: &Option<String>, strict: bool) -> PyResult<Vec<Vec<Token>>> {
+        let (document, errors) = read_to_serde_value_with_errors(path)?;
+        if strict {
+            if let Some((line_number, error)) = errors.first() {
+                return Err(PyValueError::new_err(format!("Malformed NDJSON line {}: {}", line_number, error)));
+            }
+        }
+        self.tokenize_value(&document, root)
+    }
 
-        if root.is_some() {
-            let path = root.clone().unwrap().replace(".", "/").replace("[", "/").replace("]", "").replace("$", "");
-            debug!("Pointer: {}", path);
-            document = document.pointer(&path).unwrap().clone();
+    /// Tokenize an in-memory JSON document and return a vector of tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - A reference to the JSONValue document to be tokenized.
+    /// * `root` - An optional string representing the root path of the document.
+    ///
+    /// # Returns
+    ///
+    /// A vector of tokens per record in the tokenized JSON document.
+    pub fn tokenize_value(&self, document: &JSONValue, root: &Option<String>) -> PyResult<Vec<Vec<Token>>> {
+        let mut document = document.clone();
+
+        if let Some(root) = root {
+            debug!("Resolving root path: {}", root);
+            document = Tokenizer::resolve_root(&document, root)
+                .map_err(PyValueError::new_err)?
+                .clone();
         }
 
         let mut tokens: Vec<Vec<Token>> = Vec::new();
 
         match document {
             JSONValue::Object(_) => {
-            tokens.push(Tokenizer::tokenize(&document, Some("$".to_string())));
+            tokens.push(self.tokenize(&document, Some("$".to_string())));
             }
             JSONValue::Array(arr) => {
             for value in arr.iter() {
-                tokens.push(Tokenizer::tokenize(value, Some("$".to_string())));
+                tokens.push(self.tokenize(value, Some("$".to_string())));
             }
             }
             _ => {
@@ -139,52 +309,248 @@ impl Tokenizer {
         }
 
         Ok(tokens)
-      
+
     }
 
 }
 
 
+/// Iterator yielding one vector of tokens per NDJSON record.
+///
+/// Reads the file line by line so peak memory stays bounded by the largest
+/// single record. Malformed lines are skipped, matching `read_ndjson`.
+#[pyclass(module="cleansweep_core._cleansweep_core", name="TokenStream")]
+pub struct PyTokenStream {
+    tokenizer: Tokenizer,
+    lines: io::Lines<io::BufReader<File>>,
+    root: Option<String>,
+}
+
+#[pymethods]
+impl PyTokenStream {
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> Option<Vec<PyToken>> {
+        for line in self.lines.by_ref() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => continue,
+            };
+            if let Some(tokens) = self.tokenizer.tokenize_ndjson_line(&line, &self.root) {
+                return Some(tokens.iter().map(|(key, value)| {
+                    (key.clone(), serde_value_to_pyobject(py, value))
+                }).collect());
+            }
+        }
+        None
+    }
+
+}
+
 /// Python implementation of the Tokenizer class
 #[pyclass(module="cleansweep_core._cleansweep_core", name="Tokenizer")]
-pub struct PyTokenizer;
+pub struct PyTokenizer {
+    tokenizer: Tokenizer,
+}
 
 #[pymethods]
 impl PyTokenizer {
 
     /// Create a new instance of the Tokenizer class.
+    ///
+    /// `index_style` selects the array path notation; defaults to `IndexStyle.Dot`.
     #[new]
-    fn new() -> Self {
-        PyTokenizer
+    #[pyo3(signature = (index_style=None))]
+    fn new(index_style: Option<IndexStyle>) -> Self {
+        PyTokenizer {
+            tokenizer: Tokenizer::new(index_style.unwrap_or_default()),
+        }
     }
-    
+
     /// Tokenize a JSON document and return a vector of tokens.
-    /// 
+    ///
     /// # Arguments
-    /// 
+    ///
     /// * `path` - A reference to the path of the JSON document.
     /// * `root` - An optional string representing the root path of the document.
-    /// 
+    /// * `strict` - When true, raise a `ValueError` on the first malformed
+    ///   NDJSON line instead of silently skipping it.
+    ///
     /// # Returns
-    /// 
+    ///
     /// A vector of tokens representing the tokenized JSON document.
-    /// 
+    ///
     /// # Examples
-    /// 
+    ///
     /// ```
     /// let tokenizer = Tokenizer::new();
     /// let tokens = tokenizer.tokenize_document("data.json", None);
     /// ```
-    #[pyo3(signature = (path, root=None))]
-    pub fn tokenize_document(&self, py: Python, path: String, root: Option<String>) -> PyResult<Vec<Vec<PyToken>>> {
-        let tokens = Tokenizer::tokenize_document(&path, &root).unwrap();
+    #[pyo3(signature = (path, root=None, strict=false))]
+    pub fn tokenize_document(&self, py: Python, path: String, root: Option<String>, strict: bool) -> PyResult<Vec<Vec<PyToken>>> {
+        let tokens = self.tokenizer.tokenize_document(&path, &root, strict)?;
 
         Ok(tokens.iter().map(|t| {
             t.iter().map(|(key, value)| {
                 (key.clone(), serde_value_to_pyobject(py, value))
             }).collect()
         }).collect())
-      
+
+    }
+
+    /// Tokenize an NDJSON file one record at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A reference to the path of the NDJSON file.
+    /// * `root` - An optional root path applied to each record before tokenizing.
+    ///
+    /// # Returns
+    ///
+    /// A `TokenStream` iterator yielding one vector of tokens per record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tokenizer = Tokenizer::new();
+    /// for tokens in tokenizer.tokenize_ndjson_streaming("data.ndjson", None) { ... }
+    /// ```
+    #[pyo3(signature = (path, root=None))]
+    pub fn tokenize_ndjson_streaming(&self, path: String, root: Option<String>) -> PyResult<PyTokenStream> {
+        let file = File::open(&path)?;
+        Ok(PyTokenStream {
+            tokenizer: self.tokenizer,
+            lines: io::BufReader::new(file).lines(),
+            root,
+        })
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn tokenize_array_of_objects() {
+        let document = json!({"items": [{"name": "a"}, {"name": "b"}]});
+        let tokens = Tokenizer::default().tokenize(&document, None);
+        assert_eq!(tokens, vec![
+            ("$.items.0.name".to_string(), json!("a")),
+            ("$.items.1.name".to_string(), json!("b")),
+        ]);
+    }
+
+    #[test]
+    fn tokenize_array_of_arrays() {
+        let document = json!({"rows": [["a", "b"], ["c"]]});
+        let tokens = Tokenizer::default().tokenize(&document, None);
+        assert_eq!(tokens, vec![
+            ("$.rows.0.0".to_string(), json!("a")),
+            ("$.rows.0.1".to_string(), json!("b")),
+            ("$.rows.1.0".to_string(), json!("c")),
+        ]);
+    }
+
+    #[test]
+    fn tokenize_array_of_arrays_of_objects() {
+        let document = json!({"rows": [[{"id": 1}], [{"id": 2}]]});
+        let tokens = Tokenizer::default().tokenize(&document, None);
+        assert_eq!(tokens, vec![
+            ("$.rows.0.0.id".to_string(), json!(1)),
+            ("$.rows.1.0.id".to_string(), json!(2)),
+        ]);
+    }
+
+    #[test]
+    fn tokenize_bracket_index_style() {
+        let tokenizer = Tokenizer::new(IndexStyle::Bracket);
+        let document = json!({"items": [{"name": "a"}], "rows": [["x", "y"]]});
+        let tokens = tokenizer.tokenize(&document, None);
+        assert_eq!(tokens, vec![
+            ("$.items[0].name".to_string(), json!("a")),
+            ("$.rows[0][0]".to_string(), json!("x")),
+            ("$.rows[0][1]".to_string(), json!("y")),
+        ]);
+    }
+
+    #[test]
+    fn tokenize_ndjson_line_per_record() {
+        let tokenizer = Tokenizer::default();
+        let tokens = tokenizer.tokenize_ndjson_line(r#"{"name": "a", "age": 1}"#, &None).unwrap();
+        assert_eq!(tokens, vec![
+            ("$.age".to_string(), json!(1)),
+            ("$.name".to_string(), json!("a")),
+        ]);
+
+        // malformed lines and unresolvable roots are skippable
+        assert!(tokenizer.tokenize_ndjson_line("{not json", &None).is_none());
+        assert!(tokenizer.tokenize_ndjson_line(r#"{"a": 1}"#, &Some("$.missing".to_string())).is_none());
+
+        // root is applied per record
+        let tokens = tokenizer.tokenize_ndjson_line(r#"{"data": {"name": "a"}}"#, &Some("$.data".to_string())).unwrap();
+        assert_eq!(tokens, vec![("$.name".to_string(), json!("a"))]);
+    }
+
+    #[test]
+    fn tokenize_document_strict_rejects_malformed_ndjson() {
+        let path = std::env::temp_dir().join(format!("{}-partial.ndjson", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "{\"a\": 1}\n{\"a\": 2}\n{\"a\": \n").unwrap();
+        let tokenizer = Tokenizer::default();
+
+        // lenient mode keeps the two good records
+        let tokens = tokenizer.tokenize_document(path.to_str().unwrap(), &None, false).unwrap();
+        assert_eq!(tokens.len(), 2);
+
+        // strict mode raises on the truncated line
+        assert!(tokenizer.tokenize_document(path.to_str().unwrap(), &None, true).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn resolve_valid_nested_root() {
+        let document = json!({"data": {"items": [{"name": "a"}]}});
+        let tokens = Tokenizer::default().tokenize_value(&document, &Some("$.data".to_string())).unwrap();
+        assert_eq!(tokens[0], vec![("$.items.0.name".to_string(), json!("a"))]);
+    }
+
+    #[test]
+    fn resolve_array_index_root() {
+        let document = json!({"data": [{"items": {}}, {"items": {}}, {"items": {"name": "c"}}]});
+        let tokens = Tokenizer::default().tokenize_value(&document, &Some("$.data[2].items".to_string())).unwrap();
+        assert_eq!(tokens[0], vec![("$.name".to_string(), json!("c"))]);
+    }
+
+    #[test]
+    fn resolve_missing_root_errors() {
+        let document = json!({"data": {"items": []}});
+        assert!(Tokenizer::resolve_root(&document, "$.data.missing").is_err());
+        assert!(Tokenizer::resolve_root(&document, "$.data[5]").is_err());
+        assert!(Tokenizer::default().tokenize_value(&document, &Some("$.missing".to_string())).is_err());
+    }
+
+    #[test]
+    fn resolve_key_containing_literal_dot() {
+        let document = json!({"a.b": {"name": "x"}});
+        let tokens = Tokenizer::default().tokenize_value(&document, &Some("$['a.b']".to_string())).unwrap();
+        assert_eq!(tokens[0], vec![("$.name".to_string(), json!("x"))]);
+
+        // a plain dotted path must not match the dotted key
+        assert!(Tokenizer::resolve_root(&document, "$.a.b").is_err());
+    }
+
+    #[test]
+    fn tokenize_mixed_array_of_scalars() {
+        // arrays of scalars are emitted whole so literal mappings match the array
+        let document = json!({"data": [1, "a", true]});
+        let tokens = Tokenizer::default().tokenize(&document, None);
+        assert_eq!(tokens, vec![
+            ("$.data".to_string(), json!([1, "a", true])),
+        ]);
+    }
+}
@@ -0,0 +1,192 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde_json::Value as JSONValue;
+use std::collections::BTreeSet;
+
+
+/// Render a JSON scalar as CSV cell text.
+///
+/// Strings are used as-is; other values are serialized, and null becomes an
+/// empty cell.
+fn cell_text(value: &JSONValue) -> String {
+    match value {
+        JSONValue::Null => String::new(),
+        JSONValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Join a content item's `data` array into a single block of text.
+fn joined_data(data: &JSONValue) -> String {
+    match data.as_array() {
+        Some(items) => items.iter().map(cell_text).collect::<Vec<String>>().join("\n"),
+        None => cell_text(data),
+    }
+}
+
+/// Pair a table row's cells with their column headers.
+fn table_row_text(cells: &[String], columns: &[String]) -> String {
+    cells.iter()
+        .enumerate()
+        .map(|(i, cell)| match columns.get(i) {
+            Some(column) => format!("{}: {}", column, cell),
+            None => cell.clone(),
+        })
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+/// Flatten transformed documents into a CSV string.
+///
+/// Each content item becomes one row with the columns `id`, `name`, the
+/// document metadata keys (the sorted union across all documents, so ordering
+/// is consistent), `title`, and the joined `data` text. Table content emits
+/// one row per table row, with each cell prefixed by its column header.
+///
+/// # Arguments
+///
+/// * `documents` - A slice of transformed documents as produced by `Transformer::transform`.
+///
+/// # Returns
+///
+/// A CSV string with a header row, quoted and escaped by the `csv` crate.
+pub(crate) fn documents_to_csv(documents: &[JSONValue]) -> PyResult<String> {
+    // union of metadata keys, sorted so column order does not depend on
+    // which document a key first appears in
+    let metadata_keys: Vec<String> = documents.iter()
+        .filter_map(|document| document.get("metadata"))
+        .filter_map(|metadata| metadata.as_object())
+        .flat_map(|metadata| metadata.keys().cloned())
+        .collect::<BTreeSet<String>>()
+        .into_iter()
+        .collect();
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let csv_err = |e: csv::Error| PyValueError::new_err(format!("CSV write failed: {}", e));
+
+    let mut header = vec!["id".to_string(), "name".to_string()];
+    header.extend(metadata_keys.iter().cloned());
+    header.push("title".to_string());
+    header.push("data".to_string());
+    writer.write_record(&header).map_err(csv_err)?;
+
+    for document in documents {
+        let id = cell_text(document.get("id").unwrap_or(&JSONValue::Null));
+        let name = cell_text(document.get("name").unwrap_or(&JSONValue::Null));
+        let metadata = document.get("metadata");
+        let metadata_cells: Vec<String> = metadata_keys.iter()
+            .map(|key| cell_text(metadata.and_then(|m| m.get(key)).unwrap_or(&JSONValue::Null)))
+            .collect();
+
+        let empty = Vec::new();
+        let content = document.get("content").and_then(|c| c.as_array()).unwrap_or(&empty);
+
+        let mut write_row = |title: &str, data: &str| {
+            let mut record = vec![id.clone(), name.clone()];
+            record.extend(metadata_cells.iter().cloned());
+            record.push(title.to_string());
+            record.push(data.to_string());
+            writer.write_record(&record).map_err(csv_err)
+        };
+
+        for item in content {
+            let title = cell_text(item.get("title").unwrap_or(&JSONValue::Null));
+
+            match item.get("rows").and_then(|rows| rows.as_array()) {
+                Some(rows) => {
+                    let columns: Vec<String> = item.get("columns")
+                        .and_then(|c| c.as_array())
+                        .map(|c| c.iter().map(cell_text).collect())
+                        .unwrap_or_default();
+
+                    // rows is either a list of cell lists, or a single row of scalars
+                    if rows.iter().all(|row| row.is_array()) {
+                        for row in rows {
+                            let cells: Vec<String> = row.as_array().unwrap().iter().map(cell_text).collect();
+                            write_row(&title, &table_row_text(&cells, &columns))?;
+                        }
+                    } else {
+                        let cells: Vec<String> = rows.iter().map(cell_text).collect();
+                        write_row(&title, &table_row_text(&cells, &columns))?;
+                    }
+                }
+                None => {
+                    let data = joined_data(item.get("data").unwrap_or(&JSONValue::Null));
+                    write_row(&title, &data)?;
+                }
+            }
+        }
+    }
+
+    let bytes = writer.into_inner()
+        .map_err(|e| PyValueError::new_err(format!("CSV write failed: {}", e)))?;
+    String::from_utf8(bytes)
+        .map_err(|e| PyValueError::new_err(format!("CSV output is not valid UTF-8: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn read_back(csv_text: &str) -> Vec<Vec<String>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(csv_text.as_bytes());
+        reader.records()
+            .map(|record| record.unwrap().iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn text_and_table_content_round_trip() {
+        let documents = vec![json!({
+            "id": "1",
+            "name": "doc",
+            "metadata": {"language": "en"},
+            "content": [
+                {"title": "intro", "data": ["line one", "line two"]},
+                {"title": "scores", "columns": ["name", "score"], "rows": [["a", "1"], ["b", "2"]]}
+            ]
+        })];
+
+        let csv_text = documents_to_csv(&documents).unwrap();
+        let records = read_back(&csv_text);
+
+        assert_eq!(records[0], vec!["id", "name", "language", "title", "data"]);
+        assert_eq!(records[1], vec!["1", "doc", "en", "intro", "line one\nline two"]);
+        assert_eq!(records[2], vec!["1", "doc", "en", "scores", "name: a; score: 1"]);
+        assert_eq!(records[3], vec!["1", "doc", "en", "scores", "name: b; score: 2"]);
+    }
+
+    #[test]
+    fn values_with_commas_and_newlines_are_quoted() {
+        let documents = vec![json!({
+            "id": "a,b",
+            "name": "line\nbreak",
+            "content": [{"title": "t, with comma", "data": ["x,y", "z"]}]
+        })];
+
+        let csv_text = documents_to_csv(&documents).unwrap();
+
+        // the csv crate must have quoted these; reading back restores them exactly
+        let records = read_back(&csv_text);
+        assert_eq!(records[1], vec!["a,b", "line\nbreak", "t, with comma", "x,y\nz"]);
+    }
+
+    #[test]
+    fn metadata_columns_are_consistent_across_documents() {
+        let documents = vec![
+            json!({"id": "1", "metadata": {"year": "2024"}, "content": [{"title": null, "data": ["a"]}]}),
+            json!({"id": "2", "metadata": {"author": "b"}, "content": [{"title": null, "data": ["b"]}]}),
+        ];
+
+        let csv_text = documents_to_csv(&documents).unwrap();
+        let records = read_back(&csv_text);
+
+        // sorted union of metadata keys, with empty cells where a key is absent
+        assert_eq!(records[0], vec!["id", "name", "author", "year", "title", "data"]);
+        assert_eq!(records[1], vec!["1", "", "", "2024", "", "a"]);
+        assert_eq!(records[2], vec!["2", "", "b", "", "", "b"]);
+    }
+}
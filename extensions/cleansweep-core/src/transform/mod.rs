@@ -1,2 +1,3 @@
 pub(crate) mod transformer;
-pub(crate) mod model;
\ No newline at end of file
+pub(crate) mod model;
+pub(crate) mod export;
\ No newline at end of file
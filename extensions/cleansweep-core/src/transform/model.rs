@@ -18,7 +18,7 @@ pub struct TableModel {
 }
 
 
-impl<'a, 'py> IntoPyObject<'py> for &'a TableModel {
+impl<'py> IntoPyObject<'py> for &TableModel {
     type Target = PyDict;
     type Output = Bound<'py, Self::Target>;
     type Error = Infallible;
@@ -43,7 +43,7 @@ pub struct ContentModel {
     pub metadata: Option<JSONValue>,
 }
 
-impl<'a, 'py> IntoPyObject<'py> for &'a ContentModel {
+impl<'py> IntoPyObject<'py> for &ContentModel {
     type Target = PyDict;
     type Output = Bound<'py, Self::Target>;
     type Error = Infallible;
@@ -67,7 +67,7 @@ pub enum ContentData {
     TableModel(TableModel),
 }
 
-impl<'a, 'py> IntoPyObject<'py> for &'a ContentData {
+impl<'py> IntoPyObject<'py> for &ContentData {
     type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
     type Error = Infallible;
@@ -114,7 +114,7 @@ impl Default for DocumentModel {
     }
 }
 
-impl<'a, 'py> IntoPyObject<'py> for &'a DocumentModel {
+impl<'py> IntoPyObject<'py> for &DocumentModel {
     type Target = PyDict;
     type Output = Bound<'py, Self::Target>;
     type Error = Infallible;
@@ -3,18 +3,49 @@ use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use rayon::prelude::*;
 use regex::Regex;
-use crate::tokenize::tokenizer::{Token, Tokenizer};
+use crate::tokenize::tokenizer::{IndexStyle, Token, Tokenizer};
 use serde_json::{Map, Value as JSONValue};
 use crate::common::utils::read_to_serde_value;
 use crate::common::utils::serde_value_to_pyobject;
+use std::collections::HashMap;
 
 
+/// Hash index over a token slice for literal (non-wildcard) path lookups.
+///
+/// Built once per document so the repeated `get_single_value` calls made
+/// during a transform avoid a linear scan of the full token slice; wildcard
+/// (`[*]`) paths still fall back to the regex scan.
+struct TokenIndex<'a> {
+    by_path: HashMap<&'a str, Vec<&'a JSONValue>>,
+}
+
+impl<'a> TokenIndex<'a> {
+    fn new(tokens: &'a [Token]) -> Self {
+        let mut by_path: HashMap<&'a str, Vec<&'a JSONValue>> = HashMap::with_capacity(tokens.len());
+        for (path, value) in tokens {
+            by_path.entry(path.as_str()).or_default().push(value);
+        }
+        TokenIndex { by_path }
+    }
+
+    /// Values stored under a literal path, in token order.
+    fn get(&self, path: &str) -> Option<&[&'a JSONValue]> {
+        self.by_path.get(path).map(|values| values.as_slice())
+    }
+}
 
 /// Rust implementation of the Transformer class
-pub struct Transformer;
+#[derive(Clone, Copy, Default)]
+pub struct Transformer {
+    index_style: IndexStyle,
+}
 
 impl Transformer {
 
+    pub fn new(index_style: IndexStyle) -> Self {
+        Transformer { index_style }
+    }
+
     /// Search a vector of tokens for a specific mapping.
     /// 
     /// # Arguments
@@ -35,18 +66,35 @@ impl Transformer {
     /// let results = Transformer::token_search(&tokens, &mapping, false);
     /// assert_eq!(results.unwrap().len(), 1);
     /// ```
-    fn token_search(tokens: &[Token], mapping: &str, first: bool) -> Option<Vec<JSONValue>> {
+    fn token_search(&self, tokens: &[Token], mapping: &str, first: bool, index: Option<&TokenIndex>) -> Option<Vec<JSONValue>> {
+
+        // literal lookups hit the index when one was built for this token slice
+        if !mapping.contains("[*]") {
+            if let Some(index) = index {
+                let values = index.get(mapping)?;
+                let results: Vec<JSONValue> = if first {
+                    values.first().map(|value| (*value).clone()).into_iter().collect()
+                } else {
+                    values.iter().map(|value| (*value).clone()).collect()
+                };
+                if results.is_empty() {
+                    return None;
+                }
+                return Some(results);
+            }
+        }
 
         let mut search_key = mapping.to_owned();
         let mut r = false;
         let mut re: Option<Regex> = None;
         if search_key.contains("[*]") {
             r = true;
+            // escape the literal parts so bracket indices like `[0]` match verbatim
             search_key = search_key
-            .replace("[*]", ".\\[\\d+\\]")
-            .replace("$", "\\$")
-            .replace(".", "\\.")
-            ;
+                .split("[*]")
+                .map(regex::escape)
+                .collect::<Vec<String>>()
+                .join(self.index_style.index_pattern());
             re = Some(Regex::new(&search_key).unwrap());
         }
         // debug!("search key: {}", search_key);
@@ -74,18 +122,21 @@ impl Transformer {
     }
 
     /// Reduce the number of tokens to search for a mapping.
-    /// 
+    ///
+    /// Binary searches the sorted token keys for the contiguous slice whose keys
+    /// share the mapping's literal prefix (everything before the first `[*]`).
+    ///
     /// # Arguments
-    /// 
-    /// * `tokens` - A reference to a vector of tokens.
+    ///
+    /// * `tokens` - A reference to a vector of tokens, sorted by key.
     /// * `mapping` - A reference to a string representing the mapping to search for.
-    /// 
+    ///
     /// # Returns
-    /// 
+    ///
     /// A vector of tokens representing the reduced search space.
-    /// 
+    ///
     /// # Examples
-    /// 
+    ///
     /// ```
     /// let tokens = vec![("name".to_string(), JSONValue::String("John".to_string()))];
     /// let mapping = "name".to_string();
@@ -93,32 +144,18 @@ impl Transformer {
     /// assert_eq!(reduced_tokens.len(), 1);
     /// ```
     fn reduce_tokens(tokens: &[Token], mapping: &str) -> Vec<Token> {
-        let mut mid = tokens.len() / 2;
-        let mut low = 0;
-        let mut q_low = 0;
-        let mut high = tokens.len();
-        let mut q_high = tokens.len();
-
-        let map = mapping.to_owned();
-
-        loop {
-            if tokens[mid].0 < map {
-                q_low = mid;
-            }
-
-            if tokens[mid].0 > map {
-                q_high = mid;
-            }
+        // literal prefix of the mapping: everything before the first wildcard
+        let prefix = match mapping.find("[*]") {
+            Some(pos) => &mapping[..pos],
+            None => mapping,
+        };
 
-            if !(tokens[mid].0 < map && tokens[mid].0 > map) {
-                return tokens[low..high].to_vec();
-            }
+        // keys sharing the prefix sort as a contiguous run starting at the
+        // first key >= prefix
+        let low = tokens.partition_point(|(key, _)| key.as_str() < prefix);
+        let high = low + tokens[low..].partition_point(|(key, _)| key.starts_with(prefix));
 
-            low = q_low;
-            high = q_high;
-            mid = (low + high) / 2;
-        }
-        
+        tokens[low..high].to_vec()
     }
 
     /// Returns all tokens that match a mapping.
@@ -140,24 +177,49 @@ impl Transformer {
     /// let keys = Transformer::get_all_token_keys(&tokens, &mapping);
     /// assert_eq!(keys.len(), 1);
     /// ```
-    fn get_all_tokens(tokens: &[Token], mapping: &JSONValue) -> Vec<Token> {
+    fn get_all_tokens(&self, tokens: &[Token], mapping: &JSONValue) -> Vec<Token> {
         let mut keys: Vec<Token> = Vec::new();
-        for (_, value) in mapping.as_object().unwrap() {
-            if value.is_object() {
-                // debug!("Mapping is an object.");
-                keys.extend(Transformer::get_all_tokens(tokens, value));
+        for (key, value) in mapping.as_object().unwrap() {
+            // "filter" holds predicate config, not paths to search for
+            if key == "filter" {
+                continue;
+            }
+            // object-form single-value mappings carry their search paths in
+            // `path` and `fallback`; `default` is a literal, not a path
+            let mut search_keys: Vec<String> = Vec::new();
+            if let Some(value_obj) = value.as_object() {
+                if value_obj.contains_key("path") {
+                    if let Some(path) = value_obj.get("path").and_then(|p| p.as_str()) {
+                        search_keys.push(path.to_owned());
+                    }
+                    match value_obj.get("fallback") {
+                        Some(JSONValue::String(path)) => search_keys.push(path.clone()),
+                        Some(JSONValue::Array(paths)) => {
+                            search_keys.extend(paths.iter().filter_map(|p| p.as_str().map(str::to_owned)));
+                        }
+                        _ => {}
+                    }
+                } else {
+                    // debug!("Mapping is an object.");
+                    keys.extend(self.get_all_tokens(tokens, value));
+                    continue;
+                }
             } else {
-                let mut search_key = value.as_str().unwrap().to_owned();
+                search_keys.push(value.as_str().unwrap().to_owned());
+            }
+
+            for mut search_key in search_keys {
                 let mut r = false;
                 let mut re: Option<Regex> = None;
                 let reduced_tokens = Transformer::reduce_tokens(tokens, &search_key);
                 if search_key.contains("[*]") {
                     r = true;
+                    // escape the literal parts so bracket indices like `[0]` match verbatim
                     search_key = search_key
-                    .replace("[*]", ".\\d+")
-                    .replace("$", "\\$")
-                    .replace(".", "\\.")
-                    ;
+                        .split("[*]")
+                        .map(regex::escape)
+                        .collect::<Vec<String>>()
+                        .join(self.index_style.index_pattern());
                     re = Some(Regex::new(&search_key).unwrap());
                 }
                 for token in reduced_tokens.iter() {
@@ -195,24 +257,24 @@ impl Transformer {
     /// let results = Transformer::array_search(&tokens, &mapping, &pattern);
     /// assert_eq!(results.len(), 1);
     /// ```
-    fn array_search(tokens: &[Token], mapping: &str, pattern: &str) -> Vec<Option<JSONValue>> {
+    fn array_search(&self, tokens: &[Token], mapping: &str, pattern: &str, index: Option<&TokenIndex>) -> Vec<Option<JSONValue>> {
         // debug!("Array search - mapping: {}, pattern: {}", mapping, pattern);
         let mut results: Vec<Option<JSONValue>> = Vec::new();
-        
+
         let indexes = pattern.split("|").collect::<Vec<&str>>();
         let first_index = indexes[0].parse::<usize>().unwrap();
+        let re = Regex::new(r"\[\*\]").unwrap();
 
         for i in 0..=first_index {
             // debug!("Array search index: {}", i);
-            let replacement = format!(".{}", i);
-            let re = Regex::new(r"\[\*\]").unwrap();
+            let replacement = self.index_style.index_segment(i);
             let search_token = re.replace(mapping, &replacement).to_string();
             // debug!("Array search token: {}", search_token);
 
             if indexes.len() > 1 {
-                results.extend(Transformer::array_search( tokens, &search_token, &indexes[1..].join("|")));
+                results.extend(self.array_search( tokens, &search_token, &indexes[1..].join("|"), index));
             } else {
-                let val = Transformer::token_search( tokens, &search_token, false).unwrap_or_default();
+                let val = self.token_search( tokens, &search_token, false, index).unwrap_or_default();
                 // debug!("Search iteration {} found {} results", i, val.len());
                 if !val.is_empty() {
                     results.extend(val.into_iter().map(Some));
@@ -226,18 +288,29 @@ impl Transformer {
         results
     }
 
+    /// Searches the tokens for a path and returns the first match, if any.
+    fn search_first(&self, tokens: &[Token], path: &str, index: Option<&TokenIndex>) -> Option<JSONValue> {
+        self.token_search(tokens, path, true, index)
+            .and_then(|val| val.first().cloned())
+    }
+
     /// Get a single value from a mapping.
-    /// 
+    ///
+    /// A mapping value is either a plain path string, or an object like
+    /// `{ "path": "$.title", "fallback": "$.header", "default": "Untitled" }`:
+    /// the `path` is tried first, then each `fallback` (a string or list of
+    /// strings) in order, and finally the literal `default` is returned.
+    ///
     /// # Arguments
-    /// 
+    ///
     /// * `tokens` - A reference to a vector of tokens.
     /// * `mapping` - A reference to a JSONValue representing the mapping.
     /// * `key` - A reference to a string representing the key to search for.
-    /// 
+    ///
     /// # Returns
-    /// 
+    ///
     /// An optional JSONValue representing the search result.
-    /// 
+    ///
     /// # Examples
     /// 
     /// ```
@@ -247,11 +320,10 @@ impl Transformer {
     /// let result = Transformer::get_single_value(&tokens, &mapping, &key);
     /// assert_eq!(result.unwrap().unwrap(), JSONValue::String("John".to_string()));
     /// ```
-    fn get_single_value(tokens: &[Token], mapping: &JSONValue, key: &String) -> Result<Option<JSONValue>, PyErr> {
-    
+    fn get_single_value(&self, tokens: &[Token], mapping: &JSONValue, key: &String, index: Option<&TokenIndex>) -> Result<Option<JSONValue>, PyErr> {
+
         let mapping_item = match mapping.get(key) {
             Some(value) => {
-                let value = value.as_str().unwrap();
                 value
             }
             None => {
@@ -260,19 +332,41 @@ impl Transformer {
             }
         };
 
-    
-        let val = match Transformer::token_search(tokens, mapping_item, true) {
-            Some(val) => val,
-            None => {
-                return Ok(None);
+        // object form with path/fallback/default
+        if let Some(mapping_obj) = mapping_item.as_object() {
+            let path = match mapping_obj.get("path").and_then(|p| p.as_str()) {
+                Some(path) => path,
+                None => {
+                    let err_string = format!("{} mapping object requires a 'path' string", &key);
+                    return Err(PyValueError::new_err(err_string));
+                }
+            };
+
+            if let Some(val) = self.search_first(tokens, path, index) {
+                return Ok(Some(val));
             }
-        };
 
-        if val.is_empty() {
-            return Ok(None);
+            // try each fallback path in order
+            if let Some(fallback) = mapping_obj.get("fallback") {
+                let fallbacks: Vec<&str> = match fallback {
+                    JSONValue::String(path) => vec![path.as_str()],
+                    JSONValue::Array(paths) => paths.iter().filter_map(|p| p.as_str()).collect(),
+                    _ => Vec::new(),
+                };
+                for fallback_path in fallbacks {
+                    if let Some(val) = self.search_first(tokens, fallback_path, index) {
+                        return Ok(Some(val));
+                    }
+                }
+            }
+
+            // the literal default, when provided
+            return Ok(mapping_obj.get("default").cloned());
         }
 
-        Ok(Some(val[0].clone()))
+        // plain string form
+        let mapping_item = mapping_item.as_str().unwrap();
+        Ok(self.search_first(tokens, mapping_item, index))
     }
     
     /// Search for multiple values in an array.
@@ -298,7 +392,7 @@ impl Transformer {
     /// let results = Transformer::array_search(&tokens, &mapping, &pattern, &key);
     /// assert_eq!(results.len(), 1);
     /// ```
-    fn get_array_value(tokens: &[Token], mapping: &JSONValue , pattern: &str, key:&str) -> Result<Vec<Option<JSONValue>>, PyErr> {
+    fn get_array_value(&self, tokens: &[Token], mapping: &JSONValue , pattern: &str, key:&str, index: Option<&TokenIndex>) -> Result<Vec<Option<JSONValue>>, PyErr> {
         let mapping_item = match mapping.get(key) {
             Some(value) => {
               value
@@ -309,12 +403,56 @@ impl Transformer {
         };
 
         // is my mapping_item an array?
-        if mapping_item.as_str().unwrap().contains("[*]") {
-            return Ok(Transformer::array_search(tokens, mapping_item.as_str().unwrap(), pattern));
+        if mapping_item.as_str().is_some_and(|item| item.contains("[*]")) {
+            return Ok(self.array_search(tokens, mapping_item.as_str().unwrap(), pattern, index));
+        }
+
+        // object-form mappings holding a wildcard path: try `path`, then each
+        // `fallback`, routing wildcards through array_search like plain
+        // strings; the literal `default` fills indexes that did not match
+        if let Some(mapping_obj) = mapping_item.as_object() {
+            let path = match mapping_obj.get("path").and_then(|p| p.as_str()) {
+                Some(path) => path,
+                None => {
+                    let err_string = format!("{} mapping object requires a 'path' string", &key);
+                    return Err(PyValueError::new_err(err_string));
+                }
+            };
+
+            let mut paths: Vec<&str> = vec![path];
+            match mapping_obj.get("fallback") {
+                Some(JSONValue::String(fallback)) => paths.push(fallback.as_str()),
+                Some(JSONValue::Array(fallbacks)) => paths.extend(fallbacks.iter().filter_map(|p| p.as_str())),
+                _ => {}
+            }
+
+            if paths.iter().any(|p| p.contains("[*]")) {
+                let mut results: Vec<Option<JSONValue>> = Vec::new();
+                for path in paths {
+                    results = if path.contains("[*]") {
+                        self.array_search(tokens, path, pattern, index)
+                    } else {
+                        vec![self.search_first(tokens, path, index)]
+                    };
+                    if results.iter().any(|value| value.is_some()) {
+                        break;
+                    }
+                }
+
+                if let Some(default) = mapping_obj.get("default") {
+                    for value in results.iter_mut() {
+                        if value.is_none() {
+                            *value = Some(default.clone());
+                        }
+                    }
+                }
+                return Ok(results);
+            }
+            // non-wildcard object form falls through to get_single_value
         }
 
         // if not do a normal search and return the results as a vector
-        let value = Transformer::get_single_value(tokens, mapping, &key.to_string()).unwrap();
+        let value = self.get_single_value(tokens, mapping, &key.to_string(), index)?;
         let result: Vec<Option<JSONValue>> = vec![value];
         Ok(result)
     }
@@ -340,7 +478,7 @@ impl Transformer {
     /// let metadata = Transformer::get_metadata(&tokens, &mapping);
     /// assert_eq!(metadata.unwrap().unwrap(), JSONValue::Object(Map::new()));
     /// ```
-    fn get_metadata(tokens: &[Token], mapping: &JSONValue) -> Result<Option<JSONValue>, PyErr> {
+    fn get_metadata(&self, tokens: &[Token], mapping: &JSONValue, index: Option<&TokenIndex>) -> Result<Option<JSONValue>, PyErr> {
         let mapping_item = match mapping.get("metadata") {
             Some(value) => {
               value
@@ -353,7 +491,7 @@ impl Transformer {
 
         for (key, _) in mapping_item.as_object().unwrap() {
             // debug!("Processing metadata key: {}", key);
-            let val = Transformer::get_single_value(tokens, mapping_item, &key.as_str().to_string()).unwrap();
+            let val = self.get_single_value(tokens, mapping_item, &key.as_str().to_string(), index)?;
             if let Some(val) = val {
                 metadata.as_object_mut().unwrap().insert(key.clone(), val);
             }
@@ -385,7 +523,7 @@ impl Transformer {
     /// let metadata = Transformer::get_array_metadata(&tokens, &mapping, &pattern);
     /// assert_eq!(metadata.unwrap().unwrap(), JSONValue::Object(Map::new()));
     /// ```
-    fn get_array_metadata(tokens: &[Token], mapping: &JSONValue, pattern: &str) -> Result<Option<JSONValue>, PyErr> {
+    fn get_array_metadata(&self, tokens: &[Token], mapping: &JSONValue, pattern: &str, index: Option<&TokenIndex>) -> Result<Option<JSONValue>, PyErr> {
         let mapping_item = match mapping.get("metadata") {
             Some(value) => {
               value
@@ -398,7 +536,7 @@ impl Transformer {
 
         for (key, _) in mapping_item.as_object().unwrap() {
             // debug!("Processing metadata key: {}", key);
-            let val = Transformer::get_array_value(tokens, mapping_item, pattern, key.as_str()).unwrap();
+            let val = self.get_array_value(tokens, mapping_item, pattern, key.as_str(), index)?;
             let converted_val: Vec<JSONValue> = val.iter().map(|v| if v.is_none() { JSONValue::Null } else { v.clone().unwrap() }).collect();
             metadata.as_object_mut().unwrap().insert(key.clone(), JSONValue::Array(converted_val));
         }
@@ -457,6 +595,36 @@ impl Transformer {
         max_indexes.join("|")
     }
 
+    /// Evaluates a content mapping's `filter` predicate against a built model.
+    ///
+    /// The filter names a `field` of the model - one of the already-extracted
+    /// values (`data`, `title`, or a metadata key) - and one predicate:
+    /// `equals`, `not_equals`, or `exists`. Models pass when there is no
+    /// filter, or when the filter is missing a usable `field` or predicate.
+    fn content_passes_filter(model: &JSONValue, filter: Option<&JSONValue>) -> bool {
+        let filter = match filter {
+            Some(filter) => filter,
+            None => return true,
+        };
+        let field = match filter.get("field").and_then(|f| f.as_str()) {
+            Some(field) => field,
+            None => return true,
+        };
+        let value = model.get(field);
+
+        if let Some(expected) = filter.get("equals") {
+            return value == Some(expected);
+        }
+        if let Some(expected) = filter.get("not_equals") {
+            return value != Some(expected);
+        }
+        if let Some(exists) = filter.get("exists").and_then(|e| e.as_bool()) {
+            let present = value.is_some_and(|v| !v.is_null());
+            return present == exists;
+        }
+        true
+    }
+
     /// Create an object to represent a paragraph of text.
     /// 
     /// # Arguments
@@ -465,21 +633,22 @@ impl Transformer {
     /// * `title` - A vector of optional JSONValues representing the title.
     /// * `metadata` - An optional JSONValue representing the metadata. The object contains arrays
     ///   of values for each key.
-    /// 
+    /// * `filter` - An optional filter predicate; items failing it are dropped.
+    ///
     /// # Returns
-    /// 
+    ///
     /// A vector of JSONValues representing the content.
-    /// 
+    ///
     /// # Examples
-    /// 
+    ///
     /// ```
     /// let data = vec![Some(JSONValue::String("John".to_string()))];
     /// let title = vec![Some(JSONValue::String("Name".to_string()))];
     /// let metadata = Some(JSONValue::Object(Map::new()));
-    /// let content = Transformer::create_data_content(data, title, metadata);
+    /// let content = Transformer::create_data_content(data, title, metadata, None);
     /// assert_eq!(content.len(), 1);
     /// ```
-    fn create_data_content(data: Vec<Option<JSONValue>>, title: Vec<Option<JSONValue>>, metadata: Option<JSONValue>) -> Vec<JSONValue> {
+    fn create_data_content(data: Vec<Option<JSONValue>>, title: Vec<Option<JSONValue>>, metadata: Option<JSONValue>, filter: Option<&JSONValue>) -> Vec<JSONValue> {
         let mut content = Vec::new();
         
         for (i, item) in data.iter().enumerate() {
@@ -520,7 +689,9 @@ impl Transformer {
                         };
                     }
                 }
-                content.push(model);
+                if Transformer::content_passes_filter(&model, filter) {
+                    content.push(model);
+                }
             }
         }
         content
@@ -547,10 +718,10 @@ impl Transformer {
     /// let columns = vec![Some(JSONValue::String("Name".to_string()))];
     /// let title = vec![Some(JSONValue::String("Name".to_string()))];
     /// let metadata = Some(JSONValue::Object(Map::new()));
-    /// let content = Transformer::create_table_content(rows, columns, title, metadata);
+    /// let content = Transformer::create_table_content(rows, columns, title, metadata, None);
     /// assert_eq!(content.len(), 1);
     /// ```
-    fn create_table_content(rows: Vec<Option<JSONValue>>, columns: Vec<Option<JSONValue>>, title: Vec<Option<JSONValue>>, metadata: Option<JSONValue>) -> Vec<JSONValue> {
+    fn create_table_content(rows: Vec<Option<JSONValue>>, columns: Vec<Option<JSONValue>>, title: Vec<Option<JSONValue>>, metadata: Option<JSONValue>, filter: Option<&JSONValue>) -> Vec<JSONValue> {
         let mut content = Vec::new();
         
         for (i, item) in rows.iter().enumerate() {
@@ -593,7 +764,9 @@ impl Transformer {
                         };
                     }
                 }
-                content.push(model);
+                if Transformer::content_passes_filter(&model, filter) {
+                    content.push(model);
+                }
             }
         }
         content
@@ -620,17 +793,17 @@ impl Transformer {
     /// let data = vec![Some(JSONValue::String("John".to_string()))];
     /// let title = vec![Some(JSONValue::String("Name".to_string()))];
     /// let metadata = Some(JSONValue::Object(Map::new()));
-    /// let content = Transformer::create_content(data, title, metadata);
+    /// let content = Transformer::create_content(data, title, metadata, None);
     /// assert_eq!(content.len(), 1);
     /// ```
-    fn create_content(data: Vec<Option<JSONValue>>, title: Vec<Option<JSONValue>>, columns: Vec<Option<JSONValue>>, rows: Vec<Option<JSONValue>>, metadata: Option<JSONValue>) -> Vec<JSONValue> {
-        
+    fn create_content(data: Vec<Option<JSONValue>>, title: Vec<Option<JSONValue>>, columns: Vec<Option<JSONValue>>, rows: Vec<Option<JSONValue>>, metadata: Option<JSONValue>, filter: Option<&JSONValue>) -> Vec<JSONValue> {
+
         if !data.is_empty() {
-            return Transformer::create_data_content(data, title, metadata);
+            return Transformer::create_data_content(data, title, metadata, filter);
         }
 
         if !rows.is_empty() {
-            return Transformer::create_table_content(rows, columns, title, metadata);
+            return Transformer::create_table_content(rows, columns, title, metadata, filter);
         }
         Vec::new()
     }
@@ -645,7 +818,7 @@ impl Transformer {
     /// # Returns
     /// 
     /// An optional vector of JSONValues representing the content.
-    fn get_content(tokens: &[Token], mapping: &JSONValue) -> Result<Option<Vec<JSONValue>>, PyErr> {
+    fn get_content(&self, tokens: &[Token], mapping: &JSONValue) -> Result<Option<Vec<JSONValue>>, PyErr> {
         let mapping_item = match mapping.get("content") {
             Some(value) => {
               value
@@ -665,20 +838,23 @@ impl Transformer {
         };
 
         for mapping in mapping_arr {
-            let matches = Transformer::get_all_tokens(tokens, mapping);
+            let matches = self.get_all_tokens(tokens, mapping);
 
             if matches.is_empty() {
                 continue;
             }
 
+            // the repeated per-field searches below all hit this index
+            let match_index = TokenIndex::new(&matches);
+
             let array_pattern = Transformer::get_max_indexes(&matches);
-            let data = Transformer::get_array_value(&matches, mapping, &array_pattern, "data").unwrap();
-            let title = Transformer::get_array_value(&matches, mapping, &array_pattern, "title").unwrap();
-            let columns = Transformer::get_array_value(&matches, mapping, &array_pattern, "columns").unwrap();
-            let rows = Transformer::get_array_value(&matches, mapping, &array_pattern, "rows").unwrap();
-            let metadata = Transformer::get_array_metadata(&matches, mapping, &array_pattern).unwrap();
+            let data = self.get_array_value(&matches, mapping, &array_pattern, "data", Some(&match_index))?;
+            let title = self.get_array_value(&matches, mapping, &array_pattern, "title", Some(&match_index))?;
+            let columns = self.get_array_value(&matches, mapping, &array_pattern, "columns", Some(&match_index))?;
+            let rows = self.get_array_value(&matches, mapping, &array_pattern, "rows", Some(&match_index))?;
+            let metadata = self.get_array_metadata(&matches, mapping, &array_pattern, Some(&match_index))?;
 
-            content.extend(Transformer::create_content(data, title, columns, rows, metadata));
+            content.extend(Transformer::create_content(data, title, columns, rows, metadata, mapping.get("filter")));
 
         }
         Ok(Some(content))
@@ -703,48 +879,50 @@ impl Transformer {
     /// let document = Transformer::transform(&mapping, &tokens);
     /// assert_eq!(document.id, "John");
     /// ```
-    fn transform(mapping: &JSONValue, tokens: &[Token]) -> JSONValue {
+    fn transform(&self, mapping: &JSONValue, tokens: &[Token]) -> Result<JSONValue, PyErr> {
         // let mut document = DocumentModel::default();
         let mut document = JSONValue::Object(Map::new());
-        
+
+        // built once per document and reused by every literal lookup below
+        let index = TokenIndex::new(tokens);
 
         // set id
-        let id = Transformer::get_single_value(tokens, mapping, &"id".to_string()).unwrap();
+        let id = self.get_single_value(tokens, mapping, &"id".to_string(), Some(&index))?;
         if let Some(id) = id {
-            
+
             document.as_object_mut().unwrap().insert("id".to_string(), id);
         }
 
         // set name
-        let name = Transformer::get_single_value(tokens, mapping, &"name".to_string()).unwrap();
+        let name = self.get_single_value(tokens, mapping, &"name".to_string(), Some(&index))?;
         if let Some(name) = name {
-            
+
             document.as_object_mut().unwrap().insert("name".to_string(), name);
         }
 
         // set metadata
-        let metadata = Transformer::get_metadata(tokens, mapping).unwrap();
+        let metadata = self.get_metadata(tokens, mapping, Some(&index))?;
         if let Some(metadata) = metadata {
-            
+
             document.as_object_mut().unwrap().insert("metadata".to_string(), metadata);
         }
 
         // set content
-        let content = Transformer::get_content(tokens, mapping).unwrap();
+        let content = self.get_content(tokens, mapping)?;
         if let Some(content) = content {
-            
+
             document.as_object_mut().unwrap().insert("content".to_string(), JSONValue::Array(content));
-            
+
         }
- 
-        document
+
+        Ok(document)
     }
 
-    fn transform_documents(mapping: &JSONValue, documents: &Vec<Vec<Token>>) -> Vec<JSONValue> {
-        
+    fn transform_documents(&self, mapping: &JSONValue, documents: &Vec<Vec<Token>>) -> Result<Vec<JSONValue>, PyErr> {
+
         debug!("Transforming documents...");
         documents.par_iter().map(|document| {
-            Transformer::transform(mapping, document)
+            self.transform(mapping, document)
         }).collect()
 
     }
@@ -753,25 +931,419 @@ impl Transformer {
 
 /// Python implementation of the Transformer class
 #[pyclass(module="cleansweep_core._cleansweep_core", name="Transformer")]
-pub struct PyTransformer;
+pub struct PyTransformer {
+    transformer: Transformer,
+}
 
 #[pymethods]
 impl PyTransformer {
+    /// Create a new instance of the Transformer class.
+    ///
+    /// `index_style` selects the array path notation shared with the tokenizer;
+    /// defaults to `IndexStyle.Dot`.
     #[new]
-    fn new() -> Self {
-        PyTransformer
+    #[pyo3(signature = (index_style=None))]
+    fn new(index_style: Option<IndexStyle>) -> Self {
+        PyTransformer {
+            transformer: Transformer::new(index_style.unwrap_or_default()),
+        }
     }
 
     #[pyo3(signature = (mapping_path, document_path, root=None))]
     pub fn transform_document(&self, py: Python, mapping_path: String, document_path: String, root: Option<String>) -> PyResult<Vec<PyObject>> {
-        let mapping: JSONValue = read_to_serde_value(&mapping_path);
+        let mapping: JSONValue = read_to_serde_value(&mapping_path)?;
 
-        let tokenized_documents = Tokenizer::tokenize_document(&document_path, &root).unwrap();
+        let tokenized_documents = self.tokenizer().tokenize_document(&document_path, &root, false)?;
         debug!("Documents tokenized: {:?}", tokenized_documents.len());
 
-        let res = Transformer::transform_documents(&mapping, &tokenized_documents.to_vec());
+        self.transform_tokenized(py, &mapping, &tokenized_documents)
+    }
+
+    #[pyo3(signature = (mapping_json, document_json, root=None))]
+    pub fn transform_document_from_str(&self, py: Python, mapping_json: String, document_json: String, root: Option<String>) -> PyResult<Vec<PyObject>> {
+        let mapping: JSONValue = serde_json::from_str(&mapping_json)
+            .map_err(|e| PyValueError::new_err(format!("Invalid mapping JSON: {}", e)))?;
+        let document: JSONValue = serde_json::from_str(&document_json)
+            .map_err(|e| PyValueError::new_err(format!("Invalid document JSON: {}", e)))?;
+
+        let tokenized_documents = self.tokenizer().tokenize_value(&document, &root)?;
+        debug!("Documents tokenized: {:?}", tokenized_documents.len());
+
+        self.transform_tokenized(py, &mapping, &tokenized_documents)
+    }
+
+    /// Transform a document and flatten the result into CSV.
+    ///
+    /// Returns the CSV string; when `output_path` is given, the CSV is also
+    /// written to that file.
+    #[pyo3(signature = (mapping_path, document_path, root=None, output_path=None))]
+    pub fn transform_document_to_csv(&self, mapping_path: String, document_path: String, root: Option<String>, output_path: Option<String>) -> PyResult<String> {
+        let mapping: JSONValue = read_to_serde_value(&mapping_path)?;
+
+        let tokenized_documents = self.tokenizer().tokenize_document(&document_path, &root, false)?;
+        debug!("Documents tokenized: {:?}", tokenized_documents.len());
+
+        let documents = self.transformer.transform_documents(&mapping, &tokenized_documents)?;
+        let csv_text = crate::transform::export::documents_to_csv(&documents)?;
+
+        if let Some(output_path) = output_path {
+            std::fs::write(&output_path, &csv_text)?;
+        }
+        Ok(csv_text)
+    }
+}
+
+impl PyTransformer {
+    /// Tokenizer sharing this transformer's index style.
+    fn tokenizer(&self) -> Tokenizer {
+        Tokenizer::new(self.transformer.index_style)
+    }
+
+    /// Shared tail of the file- and string-based transform methods.
+    fn transform_tokenized(&self, py: Python, mapping: &JSONValue, tokenized_documents: &[Vec<Token>]) -> PyResult<Vec<PyObject>> {
+        let res = self.transformer.transform_documents(mapping, &tokenized_documents.to_vec())?;
         let py_res: Vec<PyObject> = res.iter().map(|r| serde_value_to_pyobject(py, r)).collect();
         debug!("Documents transformed: {:?}", py_res.len());
         Ok(py_res)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_tokens() -> Vec<Token> {
+        vec![
+            ("$.a".to_string(), json!("first")),
+            ("$.items.0.name".to_string(), json!("x")),
+            ("$.items.1.name".to_string(), json!("y")),
+            ("$.itemsets".to_string(), json!("dup")),
+            ("$.z".to_string(), json!("last")),
+        ]
+    }
+
+    #[test]
+    fn reduce_tokens_wildcard_prefix() {
+        let tokens = sample_tokens();
+        let reduced = Transformer::reduce_tokens(&tokens, "$.items[*].name");
+        let keys: Vec<&str> = reduced.iter().map(|(k, _)| k.as_str()).collect();
+        // "$.itemsets" shares the literal prefix; the regex match filters it later
+        assert_eq!(keys, vec!["$.items.0.name", "$.items.1.name", "$.itemsets"]);
+    }
+
+    #[test]
+    fn reduce_tokens_literal_key_present() {
+        let tokens = sample_tokens();
+        let reduced = Transformer::reduce_tokens(&tokens, "$.z");
+        let keys: Vec<&str> = reduced.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["$.z"]);
+    }
+
+    #[test]
+    fn reduce_tokens_key_at_start() {
+        let tokens = sample_tokens();
+        let reduced = Transformer::reduce_tokens(&tokens, "$.a");
+        let keys: Vec<&str> = reduced.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["$.a"]);
+    }
+
+    #[test]
+    fn reduce_tokens_key_absent() {
+        let tokens = sample_tokens();
+        assert!(Transformer::reduce_tokens(&tokens, "$.missing").is_empty());
+        assert!(Transformer::reduce_tokens(&[], "$.a").is_empty());
+    }
+
+    #[test]
+    fn yaml_mapping_transforms_like_json_mapping() {
+        let yaml_path = std::env::temp_dir().join(format!("{}-mapping.yaml", uuid::Uuid::new_v4()));
+        std::fs::write(&yaml_path, "id: $.doc_id\nname: $.title\n").unwrap();
+        let json_mapping = json!({"id": "$.doc_id", "name": "$.title"});
+
+        let yaml_mapping = read_to_serde_value(yaml_path.to_str().unwrap()).unwrap();
+        assert_eq!(yaml_mapping, json_mapping);
+
+        let document = json!({"doc_id": "1", "title": "first"});
+        let tokens = Tokenizer::default().tokenize_value(&document, &None).unwrap();
+        let transformer = Transformer::default();
+        assert_eq!(
+            transformer.transform_documents(&yaml_mapping, &tokens).unwrap(),
+            transformer.transform_documents(&json_mapping, &tokens).unwrap()
+        );
+
+        std::fs::remove_file(yaml_path).unwrap();
+    }
+
+    #[test]
+    fn transform_from_str_matches_file_based() {
+        let mapping = json!({"id": "$.doc_id", "name": "$.title"});
+        let document_json = r#"[{"doc_id": "1", "title": "first"}, {"doc_id": "2", "title": "second"}]"#;
+
+        let path = std::env::temp_dir().join(format!("{}-doc.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, document_json).unwrap();
+
+        let tokenizer = Tokenizer::default();
+        let from_file = tokenizer.tokenize_document(path.to_str().unwrap(), &None, false).unwrap();
+        let from_str = tokenizer.tokenize_value(&serde_json::from_str(document_json).unwrap(), &None).unwrap();
+        assert_eq!(from_file, from_str);
+
+        let transformer = Transformer::default();
+        let transformed_file = transformer.transform_documents(&mapping, &from_file).unwrap();
+        let transformed_str = transformer.transform_documents(&mapping, &from_str).unwrap();
+        assert_eq!(transformed_file, transformed_str);
+        assert_eq!(transformed_str[0]["id"], json!("1"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn wildcard_mapping_matches_both_index_styles() {
+        let document = json!({"items": [{"name": "a"}, {"name": "b"}]});
+        let mapping = json!({"content": "$.items[*].name"});
+
+        for style in [IndexStyle::Dot, IndexStyle::Bracket] {
+            let tokens = Tokenizer::new(style).tokenize_value(&document, &None).unwrap();
+            let transformer = Transformer::new(style);
+            let matches = transformer.get_all_tokens(&tokens[0], &mapping);
+            let values: Vec<&JSONValue> = matches.iter().map(|(_, v)| v).collect();
+            assert_eq!(values, vec![&json!("a"), &json!("b")], "style: {:?}", style);
+        }
+    }
+
+    fn filtered_content(filter: JSONValue) -> Vec<JSONValue> {
+        let data = vec![Some(json!("body one")), Some(json!("body two"))];
+        let title = vec![Some(json!("t1")), Some(json!("t2"))];
+        let metadata = Some(json!({"status": ["published", "draft"], "reviewed": ["yes", null]}));
+        Transformer::create_data_content(data, title, metadata, Some(&filter))
+    }
+
+    fn large_synthetic_tokens(n_fields: usize) -> Vec<Token> {
+        let mut fields = serde_json::Map::new();
+        for i in 0..n_fields {
+            fields.insert(format!("field_{:05}", i), json!(format!("value-{}", i)));
+        }
+        fields.insert("items".to_string(), json!([{"name": "a"}, {"name": "b"}]));
+        let document = JSONValue::Object(fields);
+        Tokenizer::default().tokenize_value(&document, &None).unwrap().remove(0)
+    }
+
+    #[test]
+    fn indexed_token_search_matches_linear_scan() {
+        let tokens = large_synthetic_tokens(500);
+        let index = TokenIndex::new(&tokens);
+        let transformer = Transformer::default();
+
+        let mut paths: Vec<String> = (0..500).step_by(7).map(|i| format!("$.field_{:05}", i)).collect();
+        paths.push("$.missing".to_string());
+        paths.push("$.items[*].name".to_string());
+
+        for path in &paths {
+            for first in [true, false] {
+                assert_eq!(
+                    transformer.token_search(&tokens, path, first, Some(&index)),
+                    transformer.token_search(&tokens, path, first, None),
+                    "path: {}, first: {}", path, first
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn indexed_lookups_outpace_linear_scan_on_large_documents() {
+        let tokens = large_synthetic_tokens(5000);
+        let transformer = Transformer::default();
+        let paths: Vec<String> = (0..5000).step_by(3).map(|i| format!("$.field_{:05}", i)).collect();
+
+        let start = std::time::Instant::now();
+        let index = TokenIndex::new(&tokens);
+        for path in &paths {
+            transformer.token_search(&tokens, path, true, Some(&index));
+        }
+        let indexed = start.elapsed();
+
+        let start = std::time::Instant::now();
+        for path in &paths {
+            transformer.token_search(&tokens, path, true, None);
+        }
+        let scanned = start.elapsed();
+
+        // the gap is orders of magnitude, so a straight comparison is stable
+        assert!(indexed < scanned, "indexed: {:?}, scanned: {:?}", indexed, scanned);
+    }
+
+    #[test]
+    fn filter_equals_keeps_matching_items() {
+        let content = filtered_content(json!({"field": "status", "equals": "published"}));
+        assert_eq!(content.len(), 1);
+        assert_eq!(content[0]["title"], json!("t1"));
+    }
+
+    #[test]
+    fn filter_not_equals_drops_matching_items() {
+        let content = filtered_content(json!({"field": "status", "not_equals": "published"}));
+        assert_eq!(content.len(), 1);
+        assert_eq!(content[0]["title"], json!("t2"));
+    }
+
+    #[test]
+    fn filter_exists_checks_field_presence() {
+        let content = filtered_content(json!({"field": "reviewed", "exists": true}));
+        assert_eq!(content.len(), 1);
+        assert_eq!(content[0]["title"], json!("t1"));
+
+        let content = filtered_content(json!({"field": "reviewed", "exists": false}));
+        assert_eq!(content.len(), 1);
+        assert_eq!(content[0]["title"], json!("t2"));
+    }
+
+    #[test]
+    fn filter_applies_to_table_content() {
+        let rows = vec![Some(json!([["a", "1"]])), Some(json!([["b", "2"]]))];
+        let columns = vec![Some(json!(["name", "score"])), Some(json!(["name", "score"]))];
+        let title = vec![Some(json!("keep")), Some(json!("drop"))];
+        let filter = json!({"field": "title", "equals": "keep"});
+
+        let content = Transformer::create_table_content(rows, columns, title, None, Some(&filter));
+        assert_eq!(content.len(), 1);
+        assert_eq!(content[0]["title"], json!("keep"));
+    }
+
+    #[test]
+    fn filter_in_content_mapping_drops_unmatched_items() {
+        let document = json!({"sections": [
+            {"text": "keep me", "status": "published"},
+            {"text": "drop me", "status": "draft"}
+        ]});
+        let mapping = json!({"content": [{
+            "data": "$.sections[*].text",
+            "metadata": {"status": "$.sections[*].status"},
+            "filter": {"field": "status", "equals": "published"}
+        }]});
+
+        let tokens = Tokenizer::default().tokenize_value(&document, &None).unwrap();
+        let content = Transformer::default().get_content(&tokens[0], &mapping).unwrap().unwrap();
+        assert_eq!(content.len(), 1);
+        assert_eq!(content[0]["data"], json!(["keep me"]));
+    }
+
+    #[test]
+    fn object_form_mapping_inside_content_mapping() {
+        let document = json!({"sections": [{"text": "body one"}, {"text": "body two"}]});
+        let mapping = json!({"content": [{
+            "data": {"path": "$.missing[*].text", "fallback": ["$.sections[*].text"], "default": "none"}
+        }]});
+        let plain_mapping = json!({"content": [{"data": "$.sections[*].text"}]});
+
+        let tokens = Tokenizer::default().tokenize_value(&document, &None).unwrap();
+        let transformer = Transformer::default();
+
+        // a wildcard object-form mapping yields one item per match,
+        // exactly like the equivalent plain-string mapping
+        let content = transformer.get_content(&tokens[0], &mapping).unwrap().unwrap();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0]["data"], json!(["body one"]));
+        assert_eq!(content[1]["data"], json!(["body two"]));
+
+        let plain_content = transformer.get_content(&tokens[0], &plain_mapping).unwrap().unwrap();
+        assert_eq!(
+            content.iter().map(|c| &c["data"]).collect::<Vec<_>>(),
+            plain_content.iter().map(|c| &c["data"]).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn object_form_wildcard_default_fills_missing_indexes() {
+        let document = json!({"sections": [{"title": "t1"}, {"title": "t2"}]});
+        let mapping = json!({"content": [{
+            "title": "$.sections[*].title",
+            "data": {"path": "$.sections[*].text", "default": "none"}
+        }]});
+
+        let tokens = Tokenizer::default().tokenize_value(&document, &None).unwrap();
+        let content = Transformer::default().get_content(&tokens[0], &mapping).unwrap().unwrap();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0]["data"], json!(["none"]));
+        assert_eq!(content[0]["title"], json!("t1"));
+        assert_eq!(content[1]["data"], json!(["none"]));
+    }
+
+    #[test]
+    fn object_mapping_missing_path_is_an_error_not_a_panic() {
+        let tokens = sample_tokens();
+        let transformer = Transformer::default();
+
+        let mapping = json!({"metadata": {"lang": {"default": "en"}}});
+        assert!(transformer.get_metadata(&tokens, &mapping, None).is_err());
+        assert!(transformer.transform(&mapping, &tokens).is_err());
+    }
+
+    #[test]
+    fn single_value_object_mapping_path_hit() {
+        let tokens = sample_tokens();
+        let mapping = json!({"name": {"path": "$.a", "fallback": "$.z", "default": "Untitled"}});
+        let value = Transformer::default().get_single_value(&tokens, &mapping, &"name".to_string(), None).unwrap();
+        assert_eq!(value, Some(json!("first")));
+    }
+
+    #[test]
+    fn single_value_object_mapping_fallback_hit() {
+        let tokens = sample_tokens();
+        let mapping = json!({"name": {"path": "$.missing", "fallback": ["$.also_missing", "$.z"], "default": "Untitled"}});
+        let value = Transformer::default().get_single_value(&tokens, &mapping, &"name".to_string(), None).unwrap();
+        assert_eq!(value, Some(json!("last")));
+    }
+
+    #[test]
+    fn single_value_object_mapping_default_used() {
+        let tokens = sample_tokens();
+        let mapping = json!({"name": {"path": "$.missing", "fallback": "$.also_missing", "default": "Untitled"}});
+        let value = Transformer::default().get_single_value(&tokens, &mapping, &"name".to_string(), None).unwrap();
+        assert_eq!(value, Some(json!("Untitled")));
+    }
+
+    #[test]
+    fn single_value_object_mapping_without_default_is_omitted() {
+        let tokens = sample_tokens();
+        let mapping = json!({"name": {"path": "$.missing"}});
+        let value = Transformer::default().get_single_value(&tokens, &mapping, &"name".to_string(), None).unwrap();
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    fn wildcard_mapping_with_literal_index_segment() {
+        let document = json!({"data": [{"tags": [{"v": "x"}, {"v": "y"}]}, {"tags": [{"v": "z"}]}]});
+
+        // the literal `[0]`/`.0` segment must match verbatim, not as a regex class
+        for (style, mapping) in [
+            (IndexStyle::Dot, "$.data.0.tags[*].v"),
+            (IndexStyle::Bracket, "$.data[0].tags[*].v"),
+        ] {
+            let tokens = Tokenizer::new(style).tokenize_value(&document, &None).unwrap();
+            let transformer = Transformer::new(style);
+
+            let results = transformer.token_search(&tokens[0], mapping, false, None);
+            assert_eq!(results, Some(vec![json!("x"), json!("y")]), "style: {:?}", style);
+
+            let matches = transformer.get_all_tokens(&tokens[0], &json!({"content": mapping}));
+            let values: Vec<&JSONValue> = matches.iter().map(|(_, v)| v).collect();
+            assert_eq!(values, vec![&json!("x"), &json!("y")], "style: {:?}", style);
+        }
+    }
+
+    #[test]
+    fn array_search_respects_index_style() {
+        let document = json!({"items": [{"name": "a"}, {"name": "b"}]});
+
+        for style in [IndexStyle::Dot, IndexStyle::Bracket] {
+            let tokens = Tokenizer::new(style).tokenize_value(&document, &None).unwrap();
+            let transformer = Transformer::new(style);
+            let results = transformer.array_search(&tokens[0], "$.items[*].name", "1", None);
+            assert_eq!(
+                results,
+                vec![Some(json!("a")), Some(json!("b"))],
+                "style: {:?}", style
+            );
+        }
+    }
+}